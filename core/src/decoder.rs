@@ -24,16 +24,72 @@
 //! Theoretically, one could upload the deserialized decoder JSON to distribute
 //! to different applications that need the type data
 
-use super::metadata::{Metadata as RawSubstrateMetadata, ModuleMetadata};
-use runtime_version::RuntimeVersion;
-use std::collections::HashMap;
-use std::rc::Rc;
+mod block;
+mod definitions;
+mod events;
+mod extrinsics;
+mod interner;
+mod migration;
+mod names;
+mod pallet;
+mod ranges;
+mod snapshot;
+mod substrate_types;
+mod types;
+mod types_bundle;
+mod verify;
+
+pub use self::block::{
+    BlockExtrinsic, BlockView, ChangesTrieConfiguration, ChangesTrieSignal, DigestItem,
+    DispatchFailure, ExtrinsicOutcome, ExtrinsicView, GenericBlock, Header, ModuleError,
+};
+pub use self::definitions::{
+    TypeDefinition, TypeDefinitions, VariantDefinition, VersionDefinitions,
+};
+pub use self::events::{
+    dispatch_error, EventArgument, GenericEvent, Phase, EXTRINSIC_FAILED,
+    EXTRINSIC_SUCCESS, SYSTEM_MODULE,
+};
+pub use self::extrinsics::{
+    CallArgument, ExtrinsicSignature, GenericCall, GenericExtrinsic, ADDRESS_TYPE,
+    EXTRINSIC_VERSION, SIGNATURE_TYPE, SIGNED_EXTRA_TYPE,
+};
+pub use self::interner::MemoryUsage;
+pub use self::migration::{MigratedType, MigrationReport};
+pub use self::ranges::SpecRange;
+pub use self::snapshot::{
+    AliasesSnapshot, DecoderSnapshot, RangedTypeSnapshot, VersionSnapshot,
+};
+pub use self::substrate_types::SubstrateType;
+pub use self::types::{EnumVariant, RustTypeMarker, StructField, TypeMarker};
+pub(crate) use self::types::split_top_level;
+pub use self::verify::UnresolvedType;
+
+use self::events::{event_arg_marker, module_error};
+use self::extrinsics::{SIGNED_MASK, VERSION_MASK};
+use self::names::{normalize, Aliases};
+use self::ranges::RangedType;
+use super::{
+    error::Error,
+    metadata::{Metadata as RawSubstrateMetadata, ModuleMetadata},
+    RuntimeVersion,
+};
+use codec::{Compact, Decode, Encode};
+use crate::prelude::*;
+#[cfg(feature = "type-metadata")]
 use type_metadata::{
     form::{CompactForm, Form, MetaForm},
     IntoCompact, Metadata, Namespace, Registry,
 };
 
 type SpecVersion = u32;
+
+/// maximum number of names followed when resolving a type
+/// which is only an alias of another type name
+const MAX_ALIAS_DEPTH: usize = 32;
+/// maximum number of values a decoded value may be nested in
+const MAX_DECODE_DEPTH: usize = 128;
+
 /// Decoder for substrate types
 ///
 /// hold information about the Runtime Metadata
@@ -47,7 +103,19 @@ pub struct Decoder {
     /// all supported versions
     versions: Vec<SubstrateMetadata>,
     /// the type registry cache
+    #[cfg(feature = "type-metadata")]
     registry: Registry,
+    /// structural definitions of types that are not specific to any module
+    /// (IE: the `Address` and `Signature` of an extrinsic)
+    runtime_types: HashMap<SpecVersion, HashMap<String, Rc<RustTypeMarker>>>,
+    /// definitions registered for a range of spec versions,
+    /// in the order they were registered
+    ranged_types: Vec<RangedType>,
+    /// alternative names of types, for every spec version
+    aliases: Aliases,
+    /// every registered definition, shared by all versions and modules
+    /// which use it
+    interned: HashSet<Rc<RustTypeMarker>>,
 }
 
 /// holds one unit of metadata
@@ -65,7 +133,10 @@ pub struct SubstrateMetadata {
 #[derive(Debug)]
 pub struct SubstrateMetaEntry {
     /// vector holding generic type definitions of the runtime
+    #[cfg(feature = "type-metadata")]
     types: Vec<SubstrateMetaType<CompactForm>>,
+    /// structural definitions of types used to decode the module
+    markers: HashMap<String, Rc<RustTypeMarker>>,
     /// pointer to original metadata entry
     runtime_entry: Rc<ModuleMetadata>,
 }
@@ -91,7 +162,12 @@ impl Decoder {
         Self {
             types: HashMap::new(),
             versions: Vec::new(),
+            #[cfg(feature = "type-metadata")]
             registry: Registry::new(),
+            runtime_types: HashMap::new(),
+            ranged_types: Vec::new(),
+            aliases: Aliases::default(),
+            interned: HashSet::new(),
         }
     }
 
@@ -121,125 +197,669 @@ impl Decoder {
     /// so that their definitions can be decoded during runtime with
     /// SCALE codec
     ///
+    /// Only available with the `type-metadata` feature
+    ///
     /// # Panics
     ///
     /// panics when metadata coinciding with runtime version cannot be found
     /// panics when module cannot be found in the metadata
     // TODO Should return an error, not panic!
+    #[cfg(feature = "type-metadata")]
     pub fn register<T, S>(
         &mut self, version: &RuntimeVersion, module: S, type_name: &'static str,
     ) where
         S: Into<String>,
         T: Metadata,
     {
-        let module: String = module.into();
-        let raw_metadata = self
-            .get_version_metadata(version)
-            .module(&module)
-            .expect("metatadata not found"); // TODO remove panic
-
-        let type_map = match self.types.get_mut(&version.spec_version) {
-            Some(m) => m,
-            None => {
-                // create a new hashmap for a new runtime version
-                self.types
-                    .insert(version.spec_version.clone(), HashMap::new());
-                self.types
-                    .get_mut(&version.spec_version)
-                    .expect("Inserted and immediately read")
-            }
-        };
+        let ty = SubstrateMetaType::with_name_str::<T>(type_name)
+            .into_compact(&mut self.registry);
 
         // TODO check that the type_name exists in raw_metadata
         // to prevent bloating of the data structure
         // we don't want non-existant types committed
         // fixes test `should_panic_on_nonexistant_type`
+        self.entry_mut(version.spec_version, module)
+            .expect("metatadata not found") // TODO remove panic
+            .types
+            .push(ty);
+    }
 
-        if let Some(entry) = type_map.get_mut(&module) {
-            entry.types.push(
-                SubstrateMetaType::with_name_str::<T>(type_name)
-                    .into_compact(&mut self.registry),
-            )
-        } else {
-            let mut types = Vec::new();
-            types.push(
-                SubstrateMetaType::with_name_str::<T>(type_name)
-                    .into_compact(&mut self.registry),
-            );
-
-            let entry = SubstrateMetaEntry {
-                types,
-                runtime_entry: raw_metadata,
-            };
-            type_map.insert(module.into(), entry);
-        }
+    /// Register the structure of a type used by a module
+    /// so that it may be decoded dynamically
+    ///
+    /// `type_name` is the name of the type as it appears in the metadata of
+    /// the module, IE: `T::Balance` or `<T::Lookup as StaticLookup>::Source`.
+    /// Other spellings of the same name, such as `<T as Trait>::Balance` or
+    /// `Balance`, resolve to the same definition
+    ///
+    /// `pallet_types!` declares a function registering every associated type
+    /// of a pallet at once
+    pub fn register_marker<S>(
+        &mut self, version: &RuntimeVersion, module: S, type_name: S,
+        marker: RustTypeMarker,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        let type_name = normalize(&type_name.into()).into_owned();
+        let marker = self.intern(marker);
+        self.entry_mut(version.spec_version, module)?
+            .markers
+            .insert(type_name, marker);
+        Ok(())
+    }
+
+    /// Register the structure of a type which is not part of any module,
+    /// but is used by the runtime as a whole
+    ///
+    /// The types `Address`, `Signature` and `SignedExtra` must be registered
+    /// this way in order to decode signed extrinsics. Modules fall back to
+    /// runtime-wide definitions for the types they do not define
+    pub fn register_runtime_marker<S>(
+        &mut self, version: &RuntimeVersion, type_name: S, marker: RustTypeMarker,
+    ) -> Result<(), Error>
+    where
+        S: Into<String>,
+    {
+        self.version_metadata(version.spec_version)?;
+        let marker = self.intern(marker);
+        self.runtime_types
+            .entry(version.spec_version)
+            .or_insert_with(HashMap::new)
+            .insert(normalize(&type_name.into()).into_owned(), marker);
+        Ok(())
+    }
+
+    /// get the entry of a module for a runtime version,
+    /// creating it if it does not yet exist
+    fn entry_mut<S>(
+        &mut self, spec: SpecVersion, module: S,
+    ) -> Result<&mut SubstrateMetaEntry, Error>
+    where
+        S: Into<String>,
+    {
+        let module: String = module.into();
+        let raw_metadata = self.version_metadata(spec)?.module(&module)?;
+
+        let type_map = self.types.entry(spec)
+            .or_insert_with(HashMap::new);
+
+        Ok(type_map.entry(module).or_insert_with(|| SubstrateMetaEntry {
+            #[cfg(feature = "type-metadata")]
+            types: Vec::new(),
+            markers: HashMap::new(),
+            runtime_entry: raw_metadata,
+        }))
     }
 
     /// Internal API to insert a Metadata with Version attached into a sorted
     /// array
     ///
+    /// Definitions registered for a range of versions containing the new
+    /// version are applied to it
+    ///
     /// # Note
     ///
     /// all version inserts should be done before any call to
-    /// `get_version_metadata`
+    /// `version_metadata`
     fn insert_version(&mut self, sub_meta: SubstrateMetadata) {
+        let spec = sub_meta.version.spec_version;
         match self
             .versions
             .as_slice()
-            .binary_search_by_key(&sub_meta.version.spec_version, |s| {
-                s.version.spec_version
-            }) {
+            .binary_search_by_key(&spec, |s| s.version.spec_version)
+        {
             Ok(_) => (),
-            Err(i) => self.versions.insert(i, sub_meta),
+            Err(i) => {
+                self.versions.insert(i, sub_meta);
+                self.apply_ranged_types(spec);
+            }
         }
     }
 
-    /// internal api to get runtime version
-    /// panics if a version is not found
-    ///
-    /// get runtime version in less than linear time with binary search
-    ///
-    /// # Panics
-    ///
-    /// panics if the given version does not exist
-    fn get_version_metadata(&self, version: &RuntimeVersion) -> &RawSubstrateMetadata {
+    /// get the metadata of a runtime by its spec version
+    fn version_metadata(
+        &self, spec: SpecVersion,
+    ) -> Result<&RawSubstrateMetadata, Error> {
         match self
             .versions
             .as_slice()
-            .binary_search_by_key(&version.spec_version, |s| s.version.spec_version)
+            .binary_search_by_key(&spec, |s| s.version.spec_version)
         {
-            Ok(v) => &self.versions[v].metadata,
-            Err(_) => panic!("such a version does not exist"),
+            Ok(v) => Ok(&self.versions[v].metadata),
+            Err(_) => Err(Error::SpecVersionNotFound(spec)),
         }
     }
 
-    #[allow(dead_code)]
-    /// Verifies if all generic types of 'RuntimeMetadata' are present
-    fn verify(&self) -> bool {
-        // TODO: implement
-        unimplemented!()
-    }
-
     /// dynamically Decode a SCALE-encoded byte string into it's concrete rust
     /// types
+    ///
+    /// `ty` is the name of a type as it appears in the metadata of `module`
     pub fn decode(
         &self, spec: SpecVersion, module: String, ty: String, data: Vec<u8>,
-    ) {
-        // have to go to registry and get by TypeId
-        let types = self.types.get(&spec).expect("Spec does not exist");
-        let types = types.get(&module).expect("Module not found");
-
-        log::debug!("Types: {:?}", types);
+    ) -> Result<SubstrateType, Error> {
         log::debug!("Type: {}", ty);
-        // check if the concrete types are already included in
-        // RawSubstrateMetadata if not, fall back to type-metadata
-        // exported types
+        let ty = ty.parse::<RustTypeMarker>()?;
+        let mut cursor = 0;
+        let value = self.decode_single(spec, Some(&module), &ty, &data, &mut cursor, 0)?;
+        if cursor != data.len() {
+            Err(Error::TrailingBytes(data.len() - cursor))?;
+        }
+        Ok(value)
+    }
+
+    /// Decode the value of a storage entry
+    ///
+    /// `data` is the SCALE-encoded value, as returned by `state_getStorage`
+    pub fn decode_storage(
+        &self, spec: SpecVersion, module: &str, storage: &str, data: &[u8],
+    ) -> Result<SubstrateType, Error> {
+        let meta = self.version_metadata(spec)?.module(module)?;
+        let ty = meta.storage(storage)?.value_type().parse::<RustTypeMarker>()?;
+        let mut cursor = 0;
+        let value = self.decode_single(spec, Some(module), &ty, data, &mut cursor, 0)?;
+        if cursor != data.len() {
+            Err(Error::TrailingBytes(data.len() - cursor))?;
+        }
+        Ok(value)
     }
 
     /// Decode an extrinsic
-    pub fn decode_extrinsic(_ty: String, _spec: SpecVersion, _data: Vec<u8>) {
-        unimplemented!()
+    ///
+    /// `data` is the SCALE-encoded extrinsic, including its length prefix
+    pub fn decode_extrinsic(
+        &self, spec: SpecVersion, data: &[u8],
+    ) -> Result<GenericExtrinsic, Error> {
+        let mut cursor = 0;
+        let len: Compact<u32> = decode_codec(data, &mut cursor)?;
+        if data.len() - cursor != len.0 as usize {
+            Err(Error::InvalidLength(len.0 as usize, data.len() - cursor))?;
+        }
+
+        let version: u8 = decode_codec(data, &mut cursor)?;
+        let is_signed = version & SIGNED_MASK != 0;
+        let version = version & VERSION_MASK;
+        if version != EXTRINSIC_VERSION {
+            Err(Error::UnsupportedExtrinsicVersion(version))?;
+        }
+
+        let signature = if is_signed {
+            let mut runtime_type = |name: &str| {
+                let ty = RustTypeMarker::TypeName(name.to_string());
+                self.decode_single(spec, None, &ty, data, &mut cursor, 0)
+            };
+            Some(ExtrinsicSignature {
+                address: runtime_type(ADDRESS_TYPE)?,
+                signature: runtime_type(SIGNATURE_TYPE)?,
+                extra: runtime_type(SIGNED_EXTRA_TYPE)?,
+            })
+        } else {
+            None
+        };
+
+        let call = self.decode_call(spec, data, &mut cursor, 0)?;
+        if cursor != data.len() {
+            Err(Error::TrailingBytes(data.len() - cursor))?;
+        }
+        Ok(GenericExtrinsic { signature, call })
+    }
+
+    /// Decode a SCALE-encoded signed block
+    ///
+    /// The header is always decoded, extrinsics which fail to decode are
+    /// reported in the returned block rather than failing the entire block
+    pub fn decode_block(
+        &self, spec: SpecVersion, data: &[u8],
+    ) -> Result<GenericBlock, Error> {
+        let mut cursor = 0;
+        let header: Header = decode_codec(data, &mut cursor)?;
+        // every opaque extrinsic is itself a length-prefixed byte vector
+        let opaque: Vec<Vec<u8>> = decode_codec(data, &mut cursor)?;
+        let justification: Option<Vec<u8>> = decode_codec(data, &mut cursor)?;
+        if cursor != data.len() {
+            Err(Error::TrailingBytes(data.len() - cursor))?;
+        }
+
+        let extrinsics = opaque
+            .into_iter()
+            .enumerate()
+            .map(|(index, ext)| {
+                let raw = ext.encode();
+                match self.decode_extrinsic(spec, &raw) {
+                    Ok(extrinsic) => BlockExtrinsic {
+                        index,
+                        raw,
+                        extrinsic: Some(extrinsic),
+                        error: None,
+                    },
+                    Err(e) => {
+                        log::warn!("failed to decode extrinsic {}: {}", index, e);
+                        BlockExtrinsic {
+                            index,
+                            raw,
+                            extrinsic: None,
+                            error: Some(e.to_string()),
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        Ok(GenericBlock {
+            header,
+            extrinsics,
+            justification,
+        })
+    }
+
+    /// Decode the events of a block
+    ///
+    /// `data` is the SCALE-encoded `Vec<EventRecord>`
+    /// found under the `System Events` storage key
+    pub fn decode_events(
+        &self, spec: SpecVersion, data: &[u8],
+    ) -> Result<Vec<GenericEvent>, Error> {
+        let meta = self.version_metadata(spec)?;
+        let mut cursor = 0;
+        let len: Compact<u32> = decode_codec(data, &mut cursor)?;
+        // every event takes up more than one byte, so the untrusted length is
+        // bounded by the bytes left
+        let mut events =
+            Vec::with_capacity((len.0 as usize).min(data.len().saturating_sub(cursor)));
+
+        for _ in 0 .. len.0 {
+            let phase: Phase = decode_codec(data, &mut cursor)?;
+            let module_index: u8 = decode_codec(data, &mut cursor)?;
+            let event_index: u8 = decode_codec(data, &mut cursor)?;
+            let module = meta.module_by_event_index(module_index)?;
+            let event = module.event(event_index)?;
+
+            let mut args = Vec::new();
+            for arg in event.arguments() {
+                let ty = event_arg_marker(&arg)?;
+                let value = self.decode_single(
+                    spec,
+                    Some(module.name()),
+                    &ty,
+                    data,
+                    &mut cursor,
+                    0,
+                )?;
+                args.push(EventArgument {
+                    ty: arg.to_string(),
+                    value,
+                });
+            }
+            let topics: Vec<[u8; 32]> = decode_codec(data, &mut cursor)?;
+
+            events.push(GenericEvent {
+                phase,
+                module: module.name().to_string(),
+                event: event.name.clone(),
+                args,
+                topics,
+            });
+        }
+
+        if cursor != data.len() {
+            Err(Error::TrailingBytes(data.len() - cursor))?;
+        }
+        Ok(events)
+    }
+
+    /// Decode a block and the events emitted during its execution,
+    /// joining every event to the extrinsic that emitted it
+    ///
+    /// The outcome of every extrinsic is derived from the
+    /// `ExtrinsicSuccess` and `ExtrinsicFailed` events of the system module
+    pub fn decode_block_with_events(
+        &self, spec: SpecVersion, block: &[u8], events: &[u8],
+    ) -> Result<BlockView, Error> {
+        let block = self.decode_block(spec, block)?;
+        let events = self.decode_events(spec, events)?;
+
+        let mut extrinsics = block
+            .extrinsics
+            .into_iter()
+            .map(|extrinsic| ExtrinsicView {
+                extrinsic,
+                events: Vec::new(),
+                outcome: None,
+            })
+            .collect::<Vec<_>>();
+        let mut block_events = Vec::new();
+
+        for event in events.into_iter() {
+            let ext = event
+                .extrinsic_index()
+                .and_then(|i| extrinsics.get_mut(i as usize));
+            match ext {
+                Some(ext) => {
+                    if let Some(outcome) = self.extrinsic_outcome(spec, &event)? {
+                        ext.outcome = Some(outcome);
+                    }
+                    ext.events.push(event);
+                }
+                None => block_events.push(event),
+            }
+        }
+
+        Ok(BlockView {
+            header: block.header,
+            extrinsics,
+            events: block_events,
+        })
     }
+
+    /// derive the outcome of an extrinsic from one of the events it emitted
+    fn extrinsic_outcome(
+        &self, spec: SpecVersion, event: &GenericEvent,
+    ) -> Result<Option<ExtrinsicOutcome>, Error> {
+        if event.is(SYSTEM_MODULE, EXTRINSIC_SUCCESS) {
+            return Ok(Some(ExtrinsicOutcome::Success));
+        }
+        if !event.is(SYSTEM_MODULE, EXTRINSIC_FAILED) {
+            return Ok(None);
+        }
+        let error = event
+            .args
+            .first()
+            .map(|a| a.value.clone())
+            .unwrap_or(SubstrateType::Null);
+
+        let module = match module_error(&error) {
+            Some((module_index, error_index)) => {
+                let meta = self.version_metadata(spec)?;
+                let module = meta.module_by_index(module_index).ok();
+                let error = meta.module_error(module_index, error_index).ok();
+                Some(ModuleError {
+                    module_index,
+                    error_index,
+                    module: module.map(|m| m.name().to_string()),
+                    error: error.as_ref().map(|e| e.name.clone()),
+                    documentation: error.map(|e| e.documentation).unwrap_or_default(),
+                })
+            }
+            None => None,
+        };
+        Ok(Some(ExtrinsicOutcome::Failed(DispatchFailure { error, module })))
+    }
+
+    /// Decode a call, starting with the index of its module
+    fn decode_call(
+        &self, spec: SpecVersion, data: &[u8], cursor: &mut usize, depth: usize,
+    ) -> Result<GenericCall, Error> {
+        let meta = self.version_metadata(spec)?;
+        let module_index: u8 = decode_codec(data, cursor)?;
+        let call_index: u8 = decode_codec(data, cursor)?;
+        let module = meta.module_by_call_index(module_index)?;
+        let call = module.call_by_index(call_index)?;
+
+        let mut args = Vec::new();
+        for arg in call.arguments() {
+            let ty = arg.ty.parse::<RustTypeMarker>()?;
+            let value =
+                self.decode_single(spec, Some(module.name()), &ty, data, cursor, depth)?;
+            args.push(CallArgument {
+                name: arg.name.clone(),
+                value,
+            });
+        }
+        Ok(GenericCall {
+            module: module.name().to_string(),
+            call: call.name().to_string(),
+            args,
+        })
+    }
+
+    /// Decode a single value described by `ty`, starting at `cursor`
+    ///
+    /// type names are resolved against the definitions of `module`,
+    /// or against runtime-wide definitions if `module` is `None`
+    ///
+    /// `depth` is the number of values `ty` is nested in, which is bounded
+    /// so that neither nested calls nor recursive definitions overflow the
+    /// stack
+    fn decode_single(
+        &self, spec: SpecVersion, module: Option<&str>, ty: &RustTypeMarker,
+        data: &[u8], cursor: &mut usize, depth: usize,
+    ) -> Result<SubstrateType, Error> {
+        if depth >= MAX_DECODE_DEPTH {
+            Err(Error::TooDeep(MAX_DECODE_DEPTH))?;
+        }
+        let depth = depth + 1;
+        let value = match ty {
+            RustTypeMarker::TypeName(name) => {
+                let resolved = self.resolve(spec, module, name)?;
+                self.decode_single(spec, module, resolved, data, cursor, depth)?
+            }
+            RustTypeMarker::Struct(fields) => {
+                let mut values = Vec::new();
+                for field in fields {
+                    let value =
+                        self.decode_single(spec, module, &field.ty, data, cursor, depth)?;
+                    values.push((field.name.clone(), value));
+                }
+                SubstrateType::Struct(values)
+            }
+            RustTypeMarker::Tuple(types) => {
+                let mut values = Vec::new();
+                for ty in types {
+                    values
+                        .push(self.decode_single(spec, module, ty, data, cursor, depth)?);
+                }
+                SubstrateType::Composite(values)
+            }
+            RustTypeMarker::Enum(variants) => {
+                let index: u8 = decode_codec(data, cursor)?;
+                let variant = variants
+                    .get(index as usize)
+                    .ok_or(Error::InvalidEnumIndex(index))?;
+                SubstrateType::Enum {
+                    variant: variant.name.clone(),
+                    value: Box::new(
+                        self.decode_single(
+                            spec,
+                            module,
+                            &variant.ty,
+                            data,
+                            cursor,
+                            depth,
+                        )?,
+                    ),
+                }
+            }
+            RustTypeMarker::Array { size, ty } => {
+                self.decode_sequence(spec, module, ty, *size, data, cursor, depth)?
+            }
+            RustTypeMarker::Vec(ty) => {
+                let len: Compact<u32> = decode_codec(data, cursor)?;
+                let len = len.0 as usize;
+                self.decode_sequence(spec, module, ty, len, data, cursor, depth)?
+            }
+            RustTypeMarker::Option(ty) => {
+                match decode_codec::<u8>(data, cursor)? {
+                    0 => SubstrateType::Option(None),
+                    1 => SubstrateType::Option(Some(Box::new(
+                        self.decode_single(spec, module, ty, data, cursor, depth)?,
+                    ))),
+                    i => Err(Error::InvalidEnumIndex(i))?,
+                }
+            }
+            RustTypeMarker::Result(ok, err) => {
+                let (variant, ty) = match decode_codec::<u8>(data, cursor)? {
+                    0 => ("Ok", ok),
+                    1 => ("Err", err),
+                    i => Err(Error::InvalidEnumIndex(i))?,
+                };
+                SubstrateType::Enum {
+                    variant: variant.to_string(),
+                    value: Box::new(
+                        self.decode_single(spec, module, ty, data, cursor, depth)?,
+                    ),
+                }
+            }
+            RustTypeMarker::Compact(ty) => {
+                let ty = match &**ty {
+                    RustTypeMarker::TypeName(name) => self.resolve(spec, module, name)?,
+                    ty => ty,
+                };
+                match ty {
+                    RustTypeMarker::U8 => {
+                        SubstrateType::U8(decode_codec::<Compact<u8>>(data, cursor)?.0)
+                    }
+                    RustTypeMarker::U16 => {
+                        SubstrateType::U16(decode_codec::<Compact<u16>>(data, cursor)?.0)
+                    }
+                    RustTypeMarker::U32 => {
+                        SubstrateType::U32(decode_codec::<Compact<u32>>(data, cursor)?.0)
+                    }
+                    RustTypeMarker::U64 => {
+                        SubstrateType::U64(decode_codec::<Compact<u64>>(data, cursor)?.0)
+                    }
+                    RustTypeMarker::U128 => SubstrateType::U128(
+                        decode_codec::<Compact<u128>>(data, cursor)?.0,
+                    ),
+                    ty => Err(Error::InvalidType(
+                        format!("Compact<{:?}>", ty),
+                        "Only unsigned integers may be compact-encoded",
+                    ))?,
+                }
+            }
+            RustTypeMarker::Call => {
+                SubstrateType::Call(self.decode_call(spec, data, cursor, depth)?)
+            }
+            RustTypeMarker::GenericAddress => decode_address(data, cursor)?,
+            RustTypeMarker::Era => decode_era(data, cursor)?,
+            RustTypeMarker::BitVec => {
+                let bits: Compact<u32> = decode_codec(data, cursor)?;
+                // rounded up without overflowing on 32-bit platforms
+                let len = bits.0 as usize / 8 + (bits.0 % 8 != 0) as usize;
+                let ty = RustTypeMarker::U8;
+                self.decode_sequence(spec, module, &ty, len, data, cursor, depth)?
+            }
+            RustTypeMarker::U8 => SubstrateType::U8(decode_codec(data, cursor)?),
+            RustTypeMarker::U16 => SubstrateType::U16(decode_codec(data, cursor)?),
+            RustTypeMarker::U32 => SubstrateType::U32(decode_codec(data, cursor)?),
+            RustTypeMarker::U64 => SubstrateType::U64(decode_codec(data, cursor)?),
+            RustTypeMarker::U128 => SubstrateType::U128(decode_codec(data, cursor)?),
+            RustTypeMarker::I8 => SubstrateType::I8(decode_codec(data, cursor)?),
+            RustTypeMarker::I16 => SubstrateType::I16(decode_codec(data, cursor)?),
+            RustTypeMarker::I32 => SubstrateType::I32(decode_codec(data, cursor)?),
+            RustTypeMarker::I64 => SubstrateType::I64(decode_codec(data, cursor)?),
+            RustTypeMarker::I128 => SubstrateType::I128(decode_codec(data, cursor)?),
+            RustTypeMarker::Bool => SubstrateType::Bool(decode_codec(data, cursor)?),
+            RustTypeMarker::Null => SubstrateType::Null,
+        };
+        Ok(value)
+    }
+
+    /// decode `len` values of the same type
+    /// sequences of bytes are kept as bytes
+    ///
+    /// `len` is usually decoded from `data` itself, so no more values are
+    /// allocated up front than there are bytes left
+    #[allow(clippy::too_many_arguments)]
+    fn decode_sequence(
+        &self, spec: SpecVersion, module: Option<&str>, ty: &RustTypeMarker,
+        len: usize, data: &[u8], cursor: &mut usize, depth: usize,
+    ) -> Result<SubstrateType, Error> {
+        let remaining = data.len().saturating_sub(*cursor);
+        if *ty == RustTypeMarker::U8 {
+            let bytes = cursor
+                .checked_add(len)
+                .and_then(|end| data.get(*cursor .. end))
+                .ok_or(Error::InvalidLength(len, remaining))?;
+            *cursor += len;
+            return Ok(SubstrateType::Bytes(bytes.to_vec()));
+        }
+        let mut values = Vec::with_capacity(len.min(remaining));
+        for _ in 0 .. len {
+            values.push(self.decode_single(spec, module, ty, data, cursor, depth)?);
+        }
+        Ok(SubstrateType::Composite(values))
+    }
+
+    /// Resolve a type name to its structural definition
+    ///
+    /// names which only alias other names are followed
+    /// until a definition is found
+    fn resolve(
+        &self, spec: SpecVersion, module: Option<&str>, name: &str,
+    ) -> Result<&RustTypeMarker, Error> {
+        let mut name = normalize(name);
+        for _ in 0 .. MAX_ALIAS_DEPTH {
+            match self.lookup(spec, module, &name) {
+                Some(RustTypeMarker::TypeName(alias)) => name = normalize(alias),
+                Some(marker) => return Ok(marker),
+                None => {
+                    return Err(Error::TypeNotFound(
+                        module.unwrap_or("Runtime").to_string(),
+                        name.to_string(),
+                    ))
+                }
+            }
+        }
+        Err(Error::InvalidType(name.to_string(), "Too many aliases"))
+    }
+}
+
+/// decode a value which implements `Decode` from `data`, starting at `cursor`
+///
+/// the cursor is advanced by the number of bytes read
+fn decode_codec<T: Decode>(data: &[u8], cursor: &mut usize) -> Result<T, Error> {
+    let mut input = data
+        .get(*cursor ..)
+        .ok_or(Error::InvalidLength(*cursor, data.len()))?;
+    let len = input.len();
+    let value = T::decode(&mut input)?;
+    *cursor += len - input.len();
+    Ok(value)
+}
+
+/// decode an `Address` of the indices module
+fn decode_address(data: &[u8], cursor: &mut usize) -> Result<SubstrateType, Error> {
+    let (variant, value) = match decode_codec::<u8>(data, cursor)? {
+        0xff => {
+            let id: [u8; 32] = decode_codec(data, cursor)?;
+            ("Id", SubstrateType::Bytes(id.to_vec()))
+        }
+        0xfc => ("Index", SubstrateType::U16(decode_codec(data, cursor)?)),
+        0xfd => ("Index", SubstrateType::U32(decode_codec(data, cursor)?)),
+        0xfe => ("Index", SubstrateType::U64(decode_codec(data, cursor)?)),
+        i @ 0x00 ..= 0xef => ("Index", SubstrateType::U8(i)),
+        i => Err(Error::InvalidEnumIndex(i))?,
+    };
+    Ok(SubstrateType::Enum {
+        variant: variant.to_string(),
+        value: Box::new(value),
+    })
+}
+
+/// decode the era of a transaction
+fn decode_era(data: &[u8], cursor: &mut usize) -> Result<SubstrateType, Error> {
+    let first: u8 = decode_codec(data, cursor)?;
+    if first == 0 {
+        return Ok(SubstrateType::Enum {
+            variant: "Immortal".to_string(),
+            value: Box::new(SubstrateType::Null),
+        });
+    }
+    let second: u8 = decode_codec(data, cursor)?;
+    let encoded = first as u64 + ((second as u64) << 8);
+    let period = 2u64 << (encoded % (1 << 4));
+    let quantize_factor = (period >> 12).max(1);
+    let phase = (encoded >> 4) * quantize_factor;
+    if period < 4 || phase >= period {
+        Err(Error::InvalidType("Era".to_string(), "Invalid period and phase"))?;
+    }
+    Ok(SubstrateType::Enum {
+        variant: "Mortal".to_string(),
+        value: Box::new(SubstrateType::Struct(vec![
+            ("period".to_string(), SubstrateType::U64(period)),
+            ("phase".to_string(), SubstrateType::U64(phase)),
+        ])),
+    })
 }
 
 /// A type from substrate metadata.
@@ -250,6 +870,7 @@ impl Decoder {
 /// information about the alias name.
 /// The name of the type from substrates Metadata, however similar to
 /// `display_name` is not optional
+#[cfg(feature = "type-metadata")]
 #[derive(Debug)]
 pub struct SubstrateMetaType<F: Form = MetaForm> {
     ty: F::TypeId,
@@ -258,6 +879,7 @@ pub struct SubstrateMetaType<F: Form = MetaForm> {
 
 // copied from ink!
 // https://github.com/paritytech/ink/blob/master/abi/src/specs.rs#L596
+#[cfg(feature = "type-metadata")]
 impl SubstrateMetaType {
 
     /// Creates a new type specification without a display name.
@@ -313,6 +935,17 @@ impl SubstrateMetaType {
     }
 }
 
+#[cfg(feature = "type-metadata")]
+impl Clone for SubstrateMetaType<CompactForm> {
+    fn clone(&self) -> Self {
+        SubstrateMetaType {
+            ty: self.ty.clone(),
+            display_name: self.display_name.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "type-metadata")]
 impl IntoCompact for SubstrateMetaType {
     type Output = SubstrateMetaType<CompactForm>;
     fn into_compact(self, registry: &mut Registry) -> Self::Output {
@@ -329,6 +962,7 @@ mod tests {
     use crate::metadata::test_suite as meta_test_suite;
     use crate::test_suite;
 
+    #[cfg(feature = "type-metadata")]
     #[derive(Metadata)]
     #[allow(dead_code)]
     pub struct TestType {
@@ -336,6 +970,7 @@ mod tests {
         name: String,
     }
 
+    #[cfg(feature = "type-metadata")]
     #[derive(Metadata)]
     #[allow(dead_code)]
     pub struct TestType2 {
@@ -345,6 +980,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "type-metadata")]
     fn add_types() {
         let mut reg = Registry::new();
 
@@ -383,24 +1019,31 @@ mod tests {
     }
 
 
+    #[cfg(feature = "type-metadata")]
     trait TestTrait {
         type Moment: Copy + Clone + Default;
     }
 
+    #[cfg(feature = "type-metadata")]
     struct TestTraitImpl;
+    #[cfg(feature = "type-metadata")]
     impl TestTrait for TestTraitImpl {
         type Moment = u32;
     }
 
+    #[cfg(feature = "type-metadata")]
     trait TestTrait2 {
         type Precision: Copy + Clone + Default;
     }
 
+    #[cfg(feature = "type-metadata")]
     struct TestTraitImpl2;
+    #[cfg(feature = "type-metadata")]
     impl TestTrait2 for TestTraitImpl2 {
         type Precision = i128;
     }
 
+    #[cfg(feature = "type-metadata")]
     #[derive(Metadata)]
     struct TestEvent {
         some_str: String,
@@ -416,11 +1059,12 @@ mod tests {
             version: rt_version.clone(),
             metadata: meta.clone(),
         });
-        let _other_meta = decoder.get_version_metadata(&rt_version);
-        assert_eq!(meta, _other_meta.clone())
+        let other_meta = decoder.version_metadata(rt_version.spec_version).unwrap();
+        assert_eq!(&meta, other_meta)
     }
 
     #[test]
+    #[cfg(feature = "type-metadata")]
     fn should_register_types() {
         let mut decoder = Decoder::new();
         let rt_version = test_suite::mock_runtime(0);
@@ -446,6 +1090,7 @@ mod tests {
     #[should_panic]
     #[ignore] // not a feature yet
     // TODO: Make this a feature
+    #[cfg(feature = "type-metadata")]
     fn should_panic_on_nonexistant_type() {
         let mut decoder = Decoder::new();
         let rt_version = test_suite::mock_runtime(0);
@@ -457,8 +1102,268 @@ mod tests {
         decoder.register::<u32, _>(&rt_version, "TestModule0", "R::IDontExist");
     }
 
+    fn decoder_with_markers() -> Decoder {
+        let mut decoder = Decoder::new();
+        let rt_version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), rt_version.clone());
+        decoder
+            .register_marker(&rt_version, "TestModule0", "T::Moment", RustTypeMarker::U32)
+            .unwrap();
+        decoder
+            .register_runtime_marker(&rt_version, "Address", RustTypeMarker::GenericAddress)
+            .unwrap();
+        let signature = RustTypeMarker::Enum(vec![
+            EnumVariant::new("Ed25519", "H512".parse().unwrap()),
+            EnumVariant::new("Sr25519", "H512".parse().unwrap()),
+        ]);
+        decoder
+            .register_runtime_marker(&rt_version, "Signature", signature)
+            .unwrap();
+        let extra = "(Era, Compact<u32>, Compact<u128>)".parse().unwrap();
+        decoder
+            .register_runtime_marker(&rt_version, "SignedExtra", extra)
+            .unwrap();
+        decoder
+    }
+
+    #[test]
+    fn should_decode_unsigned_extrinsic() {
+        let decoder = decoder_with_markers();
+        // TestModule0::TestCall0(1337)
+        let mut ext = vec![EXTRINSIC_VERSION, 0, 0];
+        ext.extend(1337u32.encode());
+
+        let ext = decoder.decode_extrinsic(0, &ext.encode()).unwrap();
+        assert!(!ext.is_signed());
+        assert_eq!(ext.call.module, "TestModule0");
+        assert_eq!(ext.call.call, "TestCall0");
+        assert_eq!(ext.call.arg("moment"), Some(&SubstrateType::U32(1337)));
+    }
+
+    #[test]
+    fn should_decode_signed_extrinsic() {
+        let decoder = decoder_with_markers();
+        let mut ext = vec![EXTRINSIC_VERSION | SIGNED_MASK, 0xff];
+        ext.extend(&[1u8; 32]);
+        // Sr25519 signature
+        ext.push(1);
+        ext.extend(&[2u8; 64][..]);
+        // immortal era, nonce, tip
+        ext.push(0);
+        ext.extend(Compact(5u32).encode());
+        ext.extend(Compact(0u128).encode());
+        // TestModule0::TestCall1(10, [1, 2, 3])
+        ext.extend(&[0, 1]);
+        ext.extend(Compact(10u64).encode());
+        ext.extend(vec![1u8, 2, 3].encode());
+
+        let ext = decoder.decode_extrinsic(0, &ext.encode()).unwrap();
+        let signature = ext.signature.expect("extrinsic is signed");
+        assert_eq!(signature.address, SubstrateType::Enum {
+            variant: "Id".to_string(),
+            value: Box::new(SubstrateType::Bytes(vec![1u8; 32])),
+        });
+        assert_eq!(ext.call.call, "TestCall1");
+        assert_eq!(ext.call.arg("amount"), Some(&SubstrateType::U64(10)));
+        assert_eq!(
+            ext.call.arg("remark"),
+            Some(&SubstrateType::Bytes(vec![1, 2, 3]))
+        );
+    }
+
+    #[test]
+    fn should_decode_block_with_failed_extrinsic() {
+        let decoder = decoder_with_markers();
+        let header = Header {
+            parent_hash: [1u8; 32],
+            number: 6,
+            state_root: [2u8; 32],
+            extrinsics_root: [3u8; 32],
+            digest: vec![
+                DigestItem::Other(vec![0]),
+                DigestItem::AuthoritiesChange(vec![[4u8; 32]]),
+                DigestItem::ChangesTrieRoot([5u8; 32]),
+                DigestItem::SealV0(7, [6u8; 64]),
+                DigestItem::Consensus(*b"BABE", vec![0, 1]),
+                DigestItem::Seal(*b"BABE", vec![0, 1]),
+                DigestItem::PreRuntime(*b"BABE", vec![0, 1]),
+                DigestItem::ChangesTrieSignal(ChangesTrieSignal::NewConfiguration(Some(
+                    ChangesTrieConfiguration {
+                        digest_interval: 4,
+                        digest_levels: 2,
+                    },
+                ))),
+            ],
+        };
+        let mut good = vec![EXTRINSIC_VERSION, 0, 0];
+        good.extend(42u32.encode());
+        // call index does not exist
+        let bad = vec![EXTRINSIC_VERSION, 0, 9];
+
+        let mut block = header.encode();
+        block.extend(vec![good, bad].encode());
+        block.extend(None::<Vec<u8>>.encode());
+
+        let block = decoder.decode_block(0, &block).unwrap();
+        assert_eq!(block.header, header);
+        assert_eq!(block.extrinsics.len(), 2);
+        assert!(block.extrinsics[0].extrinsic.is_some());
+        assert!(block.extrinsics[1].error.is_some());
+        assert_eq!(block.failed().count(), 1);
+        serde_json::to_string(&block).unwrap();
+    }
+
+    /// encode an event record of the system module
+    fn system_event(phase: Phase, event_index: u8, args: &[u8]) -> Vec<u8> {
+        let mut record = phase.encode();
+        record.extend(&[3, event_index]);
+        record.extend(args);
+        record.extend(Vec::<[u8; 32]>::new().encode());
+        record
+    }
+
+    #[test]
+    fn should_join_events_to_extrinsics() {
+        let mut decoder = decoder_with_markers();
+        let rt_version = test_suite::mock_runtime(0);
+        decoder
+            .register_marker(&rt_version, "System", "DispatchError", dispatch_error())
+            .unwrap();
+        decoder
+            .register_marker(&rt_version, "System", "DispatchInfo", RustTypeMarker::U32)
+            .unwrap();
+
+        let header = Header {
+            parent_hash: [0u8; 32],
+            number: 1,
+            state_root: [0u8; 32],
+            extrinsics_root: [0u8; 32],
+            digest: Vec::new(),
+        };
+        let mut ext = vec![EXTRINSIC_VERSION, 0, 0];
+        ext.extend(7u32.encode());
+        let mut block = header.encode();
+        block.extend(vec![ext.clone(), ext].encode());
+        block.extend(None::<Vec<u8>>.encode());
+
+        let mut events = Compact(3u32).encode();
+        events.extend(system_event(Phase::ApplyExtrinsic(0), 0, &10u32.encode()));
+        // DispatchError::Module { index: 1, error: 2 }
+        events.extend(system_event(Phase::ApplyExtrinsic(1), 1, &[3, 1, 2, 10, 0, 0, 0]));
+        events.extend(system_event(Phase::Finalization, 0, &10u32.encode()));
+
+        let view = decoder
+            .decode_block_with_events(0, &block, &events)
+            .unwrap();
+        assert_eq!(view.extrinsics.len(), 2);
+        assert!(view.extrinsics[0].is_success());
+        assert_eq!(view.extrinsics[1].events.len(), 1);
+        match &view.extrinsics[1].outcome {
+            Some(ExtrinsicOutcome::Failed(failure)) => {
+                let module = failure.module.as_ref().expect("Module error");
+                assert_eq!(module.module_index, 1);
+                assert_eq!(module.error_index, 2);
+                assert_eq!(module.module, Some("TestModule1".to_string()));
+                assert_eq!(module.error, Some("TestError2".to_string()));
+            }
+            o => panic!("Unexpected outcome {:?}", o),
+        }
+        assert_eq!(view.events.len(), 1);
+        serde_json::to_string(&view).unwrap();
+    }
+
+    #[test]
+    fn should_reject_truncated_events() {
+        let decoder = decoder_with_markers();
+        let data = Compact(u32::max_value()).encode();
+        assert!(decoder.decode_events(0, &data).is_err());
+    }
+
+    #[test]
+    fn should_decode_storage() {
+        let decoder = decoder_with_markers();
+        let value = decoder
+            .decode_storage(0, "TestModule0", "TestStorage0", &1337u32.encode())
+            .unwrap();
+        assert_eq!(value, SubstrateType::U32(1337));
+        assert!(decoder.decode_storage(0, "TestModule0", "Nope", &[]).is_err());
+    }
+
+    #[test]
+    fn should_decode_bitvec() {
+        let decoder = decoder_with_markers();
+        // 10 bits packed into two bytes
+        let mut data = Compact(10u32).encode();
+        data.extend(&[0b1010_1010, 0b0000_0011]);
+        let value = decoder
+            .decode(0, "TestModule0".into(), "BitVec".into(), data)
+            .unwrap();
+        assert_eq!(value, SubstrateType::Bytes(vec![0b1010_1010, 0b0000_0011]));
+    }
+
+    #[test]
+    fn should_not_preallocate_untrusted_lengths() {
+        let decoder = decoder_with_markers();
+        // claims `u32::max_value()` elements, but holds only one
+        let mut data = Compact(u32::max_value()).encode();
+        data.extend(&1u32.encode());
+        for ty in &["Vec<u32>", "Vec<u8>", "BitVec"] {
+            assert!(decoder
+                .decode(0, "TestModule0".into(), ty.to_string(), data.clone())
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn should_bound_nesting_depth() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        let metadata = meta_test_suite::with_call_argument(
+            &meta_test_suite::test_metadata(),
+            "TestModule0",
+            "TestCall0",
+            "Vec<Call>",
+        );
+        decoder.register_version(metadata, version.clone());
+        // `TestCall0` batching `TestCall0` batching ... an empty batch
+        let nested_calls = |levels| {
+            let mut ext = vec![EXTRINSIC_VERSION];
+            for _ in 0 .. levels {
+                ext.extend(&[0, 0]);
+                ext.extend(Compact(1u32).encode());
+            }
+            ext.extend(&[0, 0]);
+            ext.extend(Compact(0u32).encode());
+            ext.encode()
+        };
+        assert!(decoder.decode_extrinsic(0, &nested_calls(10)).is_ok());
+        match decoder.decode_extrinsic(0, &nested_calls(100_000)) {
+            Err(Error::TooDeep(depth)) => assert_eq!(depth, MAX_DECODE_DEPTH),
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let recursive = RustTypeMarker::Struct(vec![StructField::new(
+            "a",
+            RustTypeMarker::TypeName("A".into()),
+        )]);
+        decoder
+            .register_marker(&version, "TestModule0", "A", recursive)
+            .unwrap();
+        assert!(decoder
+            .decode(0, "TestModule0".into(), "A".into(), Vec::new())
+            .is_err());
+    }
+
+    #[test]
+    fn should_not_decode_unknown_spec() {
+        let decoder = decoder_with_markers();
+        let ext = vec![EXTRINSIC_VERSION, 0, 0].encode();
+        assert!(decoder.decode_extrinsic(1, &ext).is_err());
+    }
+
     #[test]
     #[should_panic]
+    #[cfg(feature = "type-metadata")]
     fn should_panic_on_nonexistant_module() {
         let mut decoder = Decoder::new();
         let rt_version = test_suite::mock_runtime(0);
@@ -0,0 +1,227 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generic representation of a block
+//!
+//! The header of a substrate block has the same layout for every runtime
+//! using a 32-byte hash and a compact block number, and is decoded directly.
+//! Extrinsics are decoded one-by-one through the decoder, so that a single
+//! malformed or unknown extrinsic does not prevent the rest of the block from
+//! being decoded
+
+use super::{events::GenericEvent, extrinsics::GenericExtrinsic, SubstrateType};
+use codec::{Decode, Encode};
+use crate::{
+    prelude::*,
+    util::{serialize_hex, to_hex},
+};
+use serde::{Serialize, Serializer};
+
+type Hash = [u8; 32];
+type ConsensusEngineId = [u8; 4];
+type Signature = [u8; 64];
+
+/// The header of a block
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize)]
+pub struct Header {
+    #[serde(serialize_with = "serialize_hex")]
+    pub parent_hash: Hash,
+    #[codec(compact)]
+    pub number: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub state_root: Hash,
+    #[serde(serialize_with = "serialize_hex")]
+    pub extrinsics_root: Hash,
+    pub digest: Vec<DigestItem>,
+}
+
+/// An item of the header digest
+///
+/// Authorities are assumed to be 32-byte public keys, as they are for every
+/// runtime desub knows of
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum DigestItem {
+    #[codec(index = "0")]
+    Other(Vec<u8>),
+    #[codec(index = "1")]
+    AuthoritiesChange(Vec<[u8; 32]>),
+    #[codec(index = "2")]
+    ChangesTrieRoot(Hash),
+    #[codec(index = "3")]
+    SealV0(u64, Signature),
+    #[codec(index = "4")]
+    Consensus(ConsensusEngineId, Vec<u8>),
+    #[codec(index = "5")]
+    Seal(ConsensusEngineId, Vec<u8>),
+    #[codec(index = "6")]
+    PreRuntime(ConsensusEngineId, Vec<u8>),
+    #[codec(index = "7")]
+    ChangesTrieSignal(ChangesTrieSignal),
+}
+
+/// A signal to the changes trie
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize)]
+pub enum ChangesTrieSignal {
+    /// the configuration of the changes trie is changed,
+    /// `None` if the changes trie is disabled
+    NewConfiguration(Option<ChangesTrieConfiguration>),
+}
+
+/// The configuration of the changes trie
+#[derive(Debug, Clone, PartialEq, Encode, Decode, Serialize)]
+pub struct ChangesTrieConfiguration {
+    pub digest_interval: u32,
+    pub digest_levels: u32,
+}
+
+impl Serialize for DigestItem {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(1))?;
+        match self {
+            DigestItem::Other(data) => map.serialize_entry("Other", &to_hex(data))?,
+            DigestItem::AuthoritiesChange(authorities) => {
+                let authorities = authorities.iter().map(to_hex).collect::<Vec<_>>();
+                map.serialize_entry("AuthoritiesChange", &authorities)?
+            }
+            DigestItem::ChangesTrieRoot(root) => {
+                map.serialize_entry("ChangesTrieRoot", &to_hex(root))?
+            }
+            DigestItem::SealV0(slot, signature) => {
+                map.serialize_entry("SealV0", &(slot, to_hex(signature)))?
+            }
+            DigestItem::Consensus(id, data) => {
+                map.serialize_entry("Consensus", &(to_hex(id), to_hex(data)))?
+            }
+            DigestItem::Seal(id, data) => {
+                map.serialize_entry("Seal", &(to_hex(id), to_hex(data)))?
+            }
+            DigestItem::PreRuntime(id, data) => {
+                map.serialize_entry("PreRuntime", &(to_hex(id), to_hex(data)))?
+            }
+            DigestItem::ChangesTrieSignal(signal) => {
+                map.serialize_entry("ChangesTrieSignal", signal)?
+            }
+        }
+        map.end()
+    }
+}
+
+/// A decoded block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenericBlock {
+    pub header: Header,
+    /// every extrinsic of the block, in the order they appear in the block
+    pub extrinsics: Vec<BlockExtrinsic>,
+    #[serde(serialize_with = "serialize_justification")]
+    pub justification: Option<Vec<u8>>,
+}
+
+/// An extrinsic as part of a block
+///
+/// If the extrinsic could not be decoded, `extrinsic` is `None` and `error`
+/// holds the reason why decoding failed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockExtrinsic {
+    /// index of the extrinsic within the block
+    pub index: usize,
+    /// the SCALE-encoded extrinsic
+    #[serde(serialize_with = "serialize_hex")]
+    pub raw: Vec<u8>,
+    pub extrinsic: Option<GenericExtrinsic>,
+    pub error: Option<String>,
+}
+
+impl GenericBlock {
+    /// iterator over all extrinsics which failed to decode
+    pub fn failed(&self) -> impl Iterator<Item = &BlockExtrinsic> {
+        self.extrinsics.iter().filter(|e| e.error.is_some())
+    }
+}
+
+/// A block joined with the events emitted while executing it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockView {
+    pub header: Header,
+    /// every extrinsic of the block with the events it emitted
+    pub extrinsics: Vec<ExtrinsicView>,
+    /// events which were not emitted by any extrinsic
+    /// (IE: during initialization or finalization of the block)
+    pub events: Vec<GenericEvent>,
+}
+
+/// An extrinsic together with the events it emitted
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtrinsicView {
+    pub extrinsic: BlockExtrinsic,
+    pub events: Vec<GenericEvent>,
+    /// `None` if neither `ExtrinsicSuccess` nor `ExtrinsicFailed`
+    /// was emitted for the extrinsic
+    pub outcome: Option<ExtrinsicOutcome>,
+}
+
+/// Whether an extrinsic was dispatched successfully
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum ExtrinsicOutcome {
+    Success,
+    Failed(DispatchFailure),
+}
+
+/// The reason an extrinsic failed to dispatch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DispatchFailure {
+    /// the `DispatchError` as it was decoded
+    pub error: SubstrateType,
+    /// the module which returned the error,
+    /// if the error originated in a module
+    pub module: Option<ModuleError>,
+}
+
+/// An error returned by a module
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModuleError {
+    /// index of the module amongst all modules of the runtime
+    pub module_index: u8,
+    /// index of the error within the `Error` enum of the module
+    pub error_index: u8,
+    /// name of the module, if it is part of the metadata
+    pub module: Option<String>,
+    /// name of the error, if it is part of the metadata
+    pub error: Option<String>,
+    /// documentation of the error
+    pub documentation: Vec<String>,
+}
+
+impl ExtrinsicView {
+    pub fn is_success(&self) -> bool {
+        self.outcome == Some(ExtrinsicOutcome::Success)
+    }
+}
+
+fn serialize_justification<S>(
+    justification: &Option<Vec<u8>>, serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match justification {
+        Some(j) => serializer.serialize_some(&to_hex(j)),
+        None => serializer.serialize_none(),
+    }
+}
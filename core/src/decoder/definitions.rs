@@ -0,0 +1,271 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Type definitions which are loaded at runtime
+//!
+//! Instead of compiling a runtime, the types of a chain may be described in
+//! JSON, grouped by the range of spec versions they are valid for. A type is
+//! written the same way it appears in the metadata, or as a struct or enum of
+//! such types, with fields and variants in the order they are encoded:
+//!
+//! ```json
+//! {
+//!     "versions": [
+//!         {
+//!             "specs": { "from": 1019, "to": 1031 },
+//!             "types": { "Balance": "u128" },
+//!             "runtime": { "Address": "GenericAddress" },
+//!             "modules": {
+//!                 "Balances": {
+//!                     "T::Balance": "u128",
+//!                     "Reasons": { "enum": ["Fee", "Misc", "All"] },
+//!                     "Status": { "enum": [["Free", "()"], ["Reserved", "()"]] },
+//!                     "AccountData": {
+//!                         "struct": [["free", "T::Balance"], ["reserved", "T::Balance"]]
+//!                     }
+//!                 }
+//!             }
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! `types` are shared by every module and the runtime as a whole. A version
+//! without `specs` applies to every spec version. Versions are registered in
+//! order, so where their ranges overlap the later one takes precedence, and
+//! within a version the definitions of a module take precedence over shared
+//! ones
+
+use super::{Decoder, EnumVariant, RustTypeMarker, SpecRange, StructField};
+use crate::{error::Error, prelude::*};
+use alloc::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Type definitions of a chain, by range of spec versions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeDefinitions {
+    pub versions: Vec<VersionDefinitions>,
+}
+
+/// Type definitions valid for a range of spec versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionDefinitions {
+    #[serde(default = "SpecRange::all")]
+    pub specs: SpecRange,
+    /// definitions of types shared by every module and the runtime, by name
+    #[serde(default)]
+    pub types: BTreeMap<String, TypeDefinition>,
+    /// definitions of runtime-wide types, by name
+    #[serde(default)]
+    pub runtime: BTreeMap<String, TypeDefinition>,
+    /// definitions of the types of every module, by module and type name
+    #[serde(default)]
+    pub modules: BTreeMap<String, BTreeMap<String, TypeDefinition>>,
+}
+
+impl Default for VersionDefinitions {
+    fn default() -> Self {
+        VersionDefinitions {
+            specs: SpecRange::all(),
+            types: BTreeMap::new(),
+            runtime: BTreeMap::new(),
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+/// The definition of one type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TypeDefinition {
+    /// a type as it is written in the metadata, IE: `Vec<(AccountId, Balance)>`
+    Type(String),
+    /// `(name, type)` fields, in the order they are encoded
+    Struct {
+        #[serde(rename = "struct")]
+        fields: Vec<(String, TypeDefinition)>,
+    },
+    /// variants, in the order of their index
+    Enum {
+        #[serde(rename = "enum")]
+        variants: Vec<VariantDefinition>,
+    },
+}
+
+/// A variant of an enum definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum VariantDefinition {
+    /// a variant without data
+    Unit(String),
+    /// a variant holding a value of a type
+    Data(String, TypeDefinition),
+}
+
+impl TypeDefinitions {
+    /// parse type definitions from JSON
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|e| Error::InvalidDefinitions(e.to_string()))
+    }
+}
+
+impl TypeDefinition {
+    /// the structure of the type, as understood by the decoder
+    pub fn to_marker(&self) -> Result<RustTypeMarker, Error> {
+        match self {
+            TypeDefinition::Type(ty) => ty.parse(),
+            TypeDefinition::Struct { fields } => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, def)| {
+                        Ok(StructField::new(name.clone(), def.to_marker()?))
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(RustTypeMarker::Struct(fields))
+            }
+            TypeDefinition::Enum { variants } => {
+                let variants = variants
+                    .iter()
+                    .map(|variant| match variant {
+                        VariantDefinition::Unit(name) => {
+                            Ok(EnumVariant::unit(name.clone()))
+                        }
+                        VariantDefinition::Data(name, def) => {
+                            Ok(EnumVariant::new(name.clone(), def.to_marker()?))
+                        }
+                    })
+                    .collect::<Result<_, Error>>()?;
+                Ok(RustTypeMarker::Enum(variants))
+            }
+        }
+    }
+}
+
+impl Decoder {
+    /// Register type definitions for the spec versions they are valid for
+    ///
+    /// Like every definition registered for a range of versions, they apply to
+    /// versions registered before as well as after. Nothing is registered if
+    /// any of the definitions is invalid
+    pub fn register_definitions(
+        &mut self, definitions: &TypeDefinitions,
+    ) -> Result<(), Error> {
+        let mut versions = Vec::new();
+        for version in &definitions.versions {
+            let modules = version
+                .modules
+                .iter()
+                .map(|(module, types)| Ok((module.as_str(), to_markers(types)?)))
+                .collect::<Result<Vec<_>, Error>>()?;
+            versions.push((
+                version.specs,
+                to_markers(&version.types)?,
+                to_markers(&version.runtime)?,
+                modules,
+            ));
+        }
+
+        for (specs, global, runtime, modules) in versions {
+            for (name, marker) in global {
+                self.register_runtime_marker_range(specs, name, marker);
+            }
+            for (name, marker) in runtime {
+                self.register_runtime_marker_range(specs, name, marker);
+            }
+            for (module, types) in modules {
+                for (name, marker) in types {
+                    self.register_marker_range(specs, module, name, marker);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Register type definitions written in JSON
+    pub fn register_definitions_json(&mut self, json: &str) -> Result<(), Error> {
+        self.register_definitions(&TypeDefinitions::from_json(json)?)
+    }
+}
+
+fn to_markers(
+    types: &BTreeMap<String, TypeDefinition>,
+) -> Result<Vec<(&str, RustTypeMarker)>, Error> {
+    types
+        .iter()
+        .map(|(name, def)| Ok((name.as_str(), def.to_marker()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decoder::SubstrateType, test_suite};
+    use codec::Encode;
+
+    const DEFINITIONS: &str = r#"{
+        "versions": [
+            {
+                "runtime": { "Address": "GenericAddress" },
+                "modules": {
+                    "TestModule0": {
+                        "T::Moment": "u32",
+                        "Reasons": { "enum": ["Fee", ["Misc", "u8"]] },
+                        "Lock": { "struct": [["id", "[u8; 8]"], ["reasons", "Reasons"]] }
+                    }
+                }
+            },
+            {
+                "specs": { "from": 1 },
+                "modules": { "TestModule0": { "T::Moment": "u64" } }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn should_register_definitions() {
+        let mut decoder = test_suite::decoder_with_versions(2);
+        decoder.register_definitions_json(DEFINITIONS).unwrap();
+
+        let value = decoder
+            .decode_storage(0, "TestModule0", "TestStorage0", &1337u32.encode())
+            .unwrap();
+        assert_eq!(value, SubstrateType::U32(1337));
+        let value = decoder
+            .decode_storage(1, "TestModule0", "TestStorage0", &1337u64.encode())
+            .unwrap();
+        assert_eq!(value, SubstrateType::U64(1337));
+        assert_eq!(
+            decoder.resolve(1, Some("TestModule0"), "Reasons").unwrap(),
+            &RustTypeMarker::Enum(vec![
+                EnumVariant::unit("Fee"),
+                EnumVariant::new("Misc", RustTypeMarker::U8),
+            ])
+        );
+        assert_eq!(
+            decoder.resolve(0, None, "Address").unwrap(),
+            &RustTypeMarker::GenericAddress
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_definitions() {
+        let mut decoder = Decoder::new();
+        assert!(decoder.register_definitions_json("{\"versions\": 1}").is_err());
+        let invalid = r#"{ "versions": [{ "runtime": { "Address": "[u8; many]" } }] }"#;
+        assert!(decoder.register_definitions_json(invalid).is_err());
+        assert!(decoder.ranged_types.is_empty());
+    }
+}
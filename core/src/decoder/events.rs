@@ -0,0 +1,200 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generic representation of the events of a block
+//!
+//! Events are stored as a `Vec<EventRecord<Event, Hash>>` under the
+//! `System Events` storage key. Every record starts with the `Phase` the
+//! event was emitted in, which ties the event to the extrinsic at that index
+
+use super::{
+    substrate_types::SubstrateType,
+    types::{EnumVariant, RustTypeMarker, StructField},
+};
+use crate::prelude::*;
+use crate::{error::Error, metadata::EventArg, util::to_hex};
+use codec::{Decode, Encode};
+use serde::Serialize;
+
+/// Name of the module emitting extrinsic results
+pub const SYSTEM_MODULE: &str = "System";
+pub const EXTRINSIC_SUCCESS: &str = "ExtrinsicSuccess";
+pub const EXTRINSIC_FAILED: &str = "ExtrinsicFailed";
+
+/// The phase of block execution an event was emitted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, Serialize)]
+pub enum Phase {
+    /// applying the extrinsic at this index
+    ApplyExtrinsic(u32),
+    Finalization,
+    Initialization,
+}
+
+/// A decoded event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenericEvent {
+    pub phase: Phase,
+    /// name of the module which emitted the event
+    pub module: String,
+    /// name of the event
+    pub event: String,
+    /// arguments of the event, in the order they were encoded
+    pub args: Vec<EventArgument>,
+    #[serde(serialize_with = "serialize_topics")]
+    pub topics: Vec<[u8; 32]>,
+}
+
+/// One decoded argument of an event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventArgument {
+    /// the type of the argument as it appears in the metadata
+    pub ty: String,
+    pub value: SubstrateType,
+}
+
+impl GenericEvent {
+    /// returns true if this event is `event` of `module`
+    pub fn is(&self, module: &str, event: &str) -> bool {
+        self.module == module && self.event == event
+    }
+
+    /// index of the extrinsic that emitted this event, if any
+    pub fn extrinsic_index(&self) -> Option<u32> {
+        match self.phase {
+            Phase::ApplyExtrinsic(i) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+/// The layout of the `DispatchError` of the system module
+///
+/// The `&'static str` of `Other` and the `message` of `Module` are never
+/// encoded
+pub fn dispatch_error() -> RustTypeMarker {
+    RustTypeMarker::Enum(vec![
+        EnumVariant::unit("Other"),
+        EnumVariant::unit("CannotLookup"),
+        EnumVariant::unit("BadOrigin"),
+        EnumVariant::new(
+            "Module",
+            RustTypeMarker::Struct(vec![
+                StructField::new("index", RustTypeMarker::U8),
+                StructField::new("error", RustTypeMarker::U8),
+            ]),
+        ),
+    ])
+}
+
+/// convert the argument of an event to a marker which can be decoded
+///
+/// the argument is parsed the same way as the argument of a call
+pub(crate) fn event_arg_marker(arg: &EventArg) -> Result<RustTypeMarker, Error> {
+    arg.to_string().parse()
+}
+
+/// Get the `(index, error)` of the module that failed to dispatch
+/// from a decoded `DispatchError`
+///
+/// Supports both the enum `DispatchError` and the older struct with an
+/// optional `module` field
+pub(crate) fn module_error(err: &SubstrateType) -> Option<(u8, u8)> {
+    let field = |fields: &[(String, SubstrateType)], name: &str| {
+        fields.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+    };
+    match err {
+        SubstrateType::Enum { variant, value } if variant == "Module" => match &**value {
+            SubstrateType::Struct(fields) => {
+                match (field(fields, "index")?, field(fields, "error")?) {
+                    (SubstrateType::U8(i), SubstrateType::U8(e)) => Some((i, e)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        },
+        SubstrateType::Struct(fields) => {
+            match (field(fields, "module")?, field(fields, "error")?) {
+                (SubstrateType::Option(Some(i)), SubstrateType::U8(e)) => match *i {
+                    SubstrateType::U8(i) => Some((i, e)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn serialize_topics<S>(topics: &[[u8; 32]], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeSeq;
+    let mut seq = serializer.serialize_seq(Some(topics.len()))?;
+    for topic in topics {
+        seq.serialize_element(&to_hex(topic))?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_module_error() {
+        let err = SubstrateType::Enum {
+            variant: "Module".to_string(),
+            value: Box::new(SubstrateType::Struct(vec![
+                ("index".to_string(), SubstrateType::U8(5)),
+                ("error".to_string(), SubstrateType::U8(2)),
+            ])),
+        };
+        assert_eq!(module_error(&err), Some((5, 2)));
+
+        let old = SubstrateType::Struct(vec![
+            (
+                "module".to_string(),
+                SubstrateType::Option(Some(Box::new(SubstrateType::U8(5)))),
+            ),
+            ("error".to_string(), SubstrateType::U8(2)),
+        ]);
+        assert_eq!(module_error(&old), Some((5, 2)));
+
+        let bad_origin = SubstrateType::Enum {
+            variant: "BadOrigin".to_string(),
+            value: Box::new(SubstrateType::Null),
+        };
+        assert_eq!(module_error(&bad_origin), None);
+    }
+
+    #[test]
+    fn should_parse_nested_event_args() {
+        let arg: EventArg = "(AccountId, (Balance, Vec<(u8, u8)>), ())".parse().unwrap();
+        let pair = || RustTypeMarker::Tuple(vec![RustTypeMarker::U8, RustTypeMarker::U8]);
+        assert_eq!(
+            event_arg_marker(&arg).unwrap(),
+            RustTypeMarker::Tuple(vec![
+                RustTypeMarker::TypeName("AccountId".to_string()),
+                RustTypeMarker::Tuple(vec![
+                    RustTypeMarker::TypeName("Balance".to_string()),
+                    RustTypeMarker::Vec(Box::new(pair())),
+                ]),
+                RustTypeMarker::Null,
+            ])
+        );
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generic representations of extrinsics and calls,
+//! independent of any runtime
+
+use super::substrate_types::SubstrateType;
+use crate::prelude::*;
+use serde::Serialize;
+
+/// The version of the extrinsic format that desub is able to decode
+pub const EXTRINSIC_VERSION: u8 = 4;
+/// Bit of the version byte which is set when an extrinsic is signed
+pub(crate) const SIGNED_MASK: u8 = 0b1000_0000;
+/// Bits of the version byte which hold the extrinsic version
+pub(crate) const VERSION_MASK: u8 = 0b0111_1111;
+
+/// Names of the runtime-wide types that make up the signature of an extrinsic
+pub const ADDRESS_TYPE: &str = "Address";
+pub const SIGNATURE_TYPE: &str = "Signature";
+pub const SIGNED_EXTRA_TYPE: &str = "SignedExtra";
+
+/// A decoded extrinsic
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenericExtrinsic {
+    /// signature of the extrinsic, if it is signed
+    pub signature: Option<ExtrinsicSignature>,
+    /// the call the extrinsic dispatches
+    pub call: GenericCall,
+}
+
+/// The signature part of a signed extrinsic
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtrinsicSignature {
+    pub address: SubstrateType,
+    pub signature: SubstrateType,
+    pub extra: SubstrateType,
+}
+
+/// A decoded call
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GenericCall {
+    /// name of the module the call belongs to
+    pub module: String,
+    /// name of the call
+    pub call: String,
+    /// arguments of the call, in the order they were encoded
+    pub args: Vec<CallArgument>,
+}
+
+/// One decoded argument of a call
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CallArgument {
+    pub name: String,
+    pub value: SubstrateType,
+}
+
+impl GenericExtrinsic {
+    pub fn is_signed(&self) -> bool {
+        self.signature.is_some()
+    }
+}
+
+impl GenericCall {
+    /// get the value of an argument by its name
+    pub fn arg(&self, name: &str) -> Option<&SubstrateType> {
+        self.args.iter().find(|a| a.name == name).map(|a| &a.value)
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Definitions shared between runtime versions
+//!
+//! Most definitions are identical across many runtime versions, as well as
+//! across the modules of a version. Every definition registered in a decoder
+//! is interned, so that it is stored once however many versions and modules
+//! use it. Types registered through `type-metadata` are already interned by
+//! the `Registry` of the decoder.
+
+use super::{Decoder, EnumVariant, RustTypeMarker, SpecVersion, StructField};
+use crate::prelude::*;
+use core::mem::size_of;
+use serde::Serialize;
+
+/// Approximate memory used by the definitions of a runtime version
+///
+/// The metadata of the version and types registered through
+/// `type-metadata` are not accounted for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryUsage {
+    pub spec: SpecVersion,
+    /// number of names defined by the modules of the version
+    /// and the runtime as a whole
+    pub names: usize,
+    /// bytes used by the names, and the tables holding them
+    pub name_bytes: usize,
+    /// bytes used by the distinct definitions the names refer to
+    pub definition_bytes: usize,
+    /// bytes of `definition_bytes` which are used by other versions as well,
+    /// and stored only once
+    pub shared_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// bytes used by this version only
+    pub fn own_bytes(&self) -> usize {
+        self.name_bytes + self.definition_bytes - self.shared_bytes
+    }
+}
+
+impl Decoder {
+    /// the shared copy of `marker`
+    pub(super) fn intern(&mut self, marker: RustTypeMarker) -> Rc<RustTypeMarker> {
+        if let Some(interned) = self.interned.get(&marker) {
+            return interned.clone();
+        }
+        let interned = Rc::new(marker);
+        self.interned.insert(interned.clone());
+        interned
+    }
+
+    /// Approximate memory used by the definitions of every registered
+    /// version, by increasing spec version
+    pub fn memory_usage(&self) -> Vec<MemoryUsage> {
+        let specs = self
+            .versions
+            .iter()
+            .map(|v| v.version.spec_version)
+            .collect::<Vec<_>>();
+
+        // distinct definitions of every version, and the number of versions
+        // using each of them
+        let mut definitions = Vec::with_capacity(specs.len());
+        let mut users = HashMap::new();
+        for &spec in &specs {
+            let mut distinct = HashMap::new();
+            for (_, marker) in self.named_definitions(spec) {
+                distinct.insert(&**marker as *const RustTypeMarker, &**marker);
+            }
+            for ptr in distinct.keys() {
+                *users.entry(*ptr).or_insert(0) += 1;
+            }
+            definitions.push(distinct);
+        }
+
+        specs
+            .into_iter()
+            .zip(definitions)
+            .map(|(spec, distinct)| {
+                let names = self.named_definitions(spec);
+                let mut usage = MemoryUsage {
+                    spec,
+                    names: names.len(),
+                    name_bytes: names
+                        .iter()
+                        .map(|(name, _)| {
+                            size_of::<(String, Rc<RustTypeMarker>)>() + name.capacity()
+                        })
+                        .sum(),
+                    ..Default::default()
+                };
+                for (ptr, marker) in distinct {
+                    let bytes = interned_size(marker);
+                    usage.definition_bytes += bytes;
+                    if users[&ptr] > 1 {
+                        usage.shared_bytes += bytes;
+                    }
+                }
+                usage
+            })
+            .collect()
+    }
+
+    /// every name defined for `spec`, with its definition
+    fn named_definitions(
+        &self, spec: SpecVersion,
+    ) -> Vec<(&String, &Rc<RustTypeMarker>)> {
+        let runtime = self.runtime_types.get(&spec).into_iter().flatten();
+        let modules = self
+            .types
+            .get(&spec)
+            .into_iter()
+            .flat_map(|modules| modules.values())
+            .flat_map(|entry| entry.markers.iter());
+        runtime.chain(modules).collect()
+    }
+}
+
+/// bytes used by an interned definition, including its reference counts
+fn interned_size(marker: &RustTypeMarker) -> usize {
+    2 * size_of::<usize>() + size_of::<RustTypeMarker>() + heap_size(marker)
+}
+
+/// bytes `marker` allocates on the heap
+fn heap_size(marker: &RustTypeMarker) -> usize {
+    let boxed = |ty: &RustTypeMarker| size_of::<RustTypeMarker>() + heap_size(ty);
+    match marker {
+        RustTypeMarker::TypeName(name) => name.capacity(),
+        RustTypeMarker::Struct(fields) => {
+            fields.capacity() * size_of::<StructField>()
+                + fields
+                    .iter()
+                    .map(|f| f.name.capacity() + heap_size(&f.ty))
+                    .sum::<usize>()
+        }
+        RustTypeMarker::Enum(variants) => {
+            variants.capacity() * size_of::<EnumVariant>()
+                + variants
+                    .iter()
+                    .map(|v| v.name.capacity() + heap_size(&v.ty))
+                    .sum::<usize>()
+        }
+        RustTypeMarker::Tuple(types) => {
+            types.capacity() * size_of::<RustTypeMarker>()
+                + types.iter().map(heap_size).sum::<usize>()
+        }
+        RustTypeMarker::Array { ty, .. }
+        | RustTypeMarker::Vec(ty)
+        | RustTypeMarker::Option(ty)
+        | RustTypeMarker::Compact(ty) => boxed(ty),
+        RustTypeMarker::Result(ok, err) => boxed(ok) + boxed(err),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_suite;
+
+    #[test]
+    fn should_share_definitions_between_versions() {
+        let mut decoder = test_suite::decoder_with_versions(3);
+        let moment = RustTypeMarker::Struct(vec![
+            StructField::new("secs", RustTypeMarker::U64),
+            StructField::new("nanos", RustTypeMarker::U32),
+        ]);
+        decoder.register_marker_range(.., "TestModule0", "T::Moment", moment.clone());
+        // registered separately, yet identical
+        decoder
+            .register_marker(
+                &test_suite::mock_runtime(0),
+                "TestModule1",
+                "T::Moment",
+                moment,
+            )
+            .unwrap();
+        let module0 = &decoder.types[&2]["TestModule0"].markers["T::Moment"];
+        let module1 = &decoder.types[&0]["TestModule1"].markers["T::Moment"];
+        assert!(Rc::ptr_eq(module0, module1));
+
+        decoder
+            .register_runtime_marker(
+                &test_suite::mock_runtime(0),
+                "Address",
+                RustTypeMarker::GenericAddress,
+            )
+            .unwrap();
+        let usage = decoder.memory_usage();
+        assert_eq!(usage.len(), 3);
+        assert_eq!(usage[0].names, 3);
+        assert!(usage[0].shared_bytes < usage[0].definition_bytes);
+        for usage in &usage[1 ..] {
+            assert_eq!(usage.names, 1);
+            assert_eq!(usage.shared_bytes, usage.definition_bytes);
+            assert_eq!(usage.own_bytes(), usage.name_bytes);
+        }
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Carry type registrations over from one runtime version to the next
+//!
+//! A type registered for a module is inherited if every call, event,
+//! storage entry and constant of the module which mentions the type has the
+//! same signature in both versions. Types which are not mentioned by the
+//! metadata at all (IE: only referenced by other registered types) are
+//! inherited as long as the module still exists.
+//! Runtime-wide types are always inherited
+
+use super::{
+    names::normalize, Decoder, SpecVersion, SubstrateMetaEntry, SubstrateMetadata,
+};
+use crate::{
+    metadata::{Change, Metadata as RawSubstrateMetadata, MetadataDiff},
+    prelude::*,
+    RuntimeVersion,
+};
+use serde::Serialize;
+
+/// The outcome of registering a version with registrations inherited from
+/// the previous version
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MigrationReport {
+    /// spec version the registrations were inherited from,
+    /// `None` if there is no earlier version
+    pub from: Option<SpecVersion>,
+    /// changes between the metadata of both versions
+    pub diff: MetadataDiff,
+    /// types which were inherited
+    pub inherited: Vec<MigratedType>,
+    /// types which were not inherited because a signature mentioning them
+    /// changed or their module no longer exists, and must be registered
+    /// again, as well as types used by added modules which do not resolve
+    pub needs_attention: Vec<MigratedType>,
+}
+
+/// A type registered for a module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigratedType {
+    pub module: String,
+    /// name of the type as it appears in the metadata
+    pub ty: String,
+}
+
+impl MigratedType {
+    fn new<S: Into<String>>(module: S, ty: S) -> Self {
+        MigratedType {
+            module: module.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+impl Decoder {
+    /// register a version, inheriting the type registrations of the closest
+    /// earlier version for every type whose usage in the metadata did not
+    /// change
+    ///
+    /// Returns which types were inherited and which need to be
+    /// registered again. Nothing changes if the version is already
+    /// registered, and the report is empty
+    pub fn register_version_migrating(
+        &mut self, metadata: RawSubstrateMetadata, version: RuntimeVersion,
+    ) -> MigrationReport {
+        let spec = version.spec_version;
+        if self.version_metadata(spec).is_ok() {
+            return MigrationReport::default();
+        }
+        let previous = self
+            .versions
+            .iter()
+            .rev()
+            .find(|v| v.version.spec_version < spec)
+            .map(|v| v.version.spec_version);
+
+        let previous = match previous {
+            Some(p) => p,
+            None => {
+                self.insert_version(SubstrateMetadata { version, metadata });
+                return MigrationReport::default();
+            }
+        };
+
+        let old_metadata = self
+            .version_metadata(previous)
+            .expect("version was just found; qed");
+        let diff = old_metadata.diff(&metadata);
+        let mut report = MigrationReport {
+            from: Some(previous),
+            diff,
+            ..Default::default()
+        };
+
+        let mut new_types = HashMap::new();
+        if let Some(old_types) = self.types.get(&previous) {
+            for (old_name, old_entry) in old_types {
+                let new_name = renamed(&report.diff, old_name);
+                let new_module = match metadata.module(new_name) {
+                    Ok(m) => m,
+                    Err(_) => {
+                        for ty in old_entry.markers.keys() {
+                            report
+                                .needs_attention
+                                .push(MigratedType::new(old_name.as_str(), ty.as_str()));
+                        }
+                        continue;
+                    }
+                };
+                let old_signatures = old_entry.runtime_entry.signatures();
+                let new_signatures = new_module.signatures();
+
+                let mut markers = HashMap::new();
+                for (ty, marker) in &old_entry.markers {
+                    let migrated = MigratedType::new(new_name, ty.as_str());
+                    if mentioned_by(ty, &old_signatures)
+                        == mentioned_by(ty, &new_signatures)
+                    {
+                        markers.insert(ty.clone(), marker.clone());
+                        report.inherited.push(migrated);
+                    } else {
+                        report.needs_attention.push(migrated);
+                    }
+                }
+                // type-metadata registrations do not keep the name they
+                // were registered with, so are only inherited if nothing in
+                // the module changed
+                #[cfg(feature = "type-metadata")]
+                let types = if unchanged(&report.diff, new_name) {
+                    old_entry.types.clone()
+                } else {
+                    Vec::new()
+                };
+
+                new_types.insert(
+                    new_name.to_string(),
+                    SubstrateMetaEntry {
+                        #[cfg(feature = "type-metadata")]
+                        types,
+                        markers,
+                        runtime_entry: new_module,
+                    },
+                );
+            }
+        }
+
+        if let Some(runtime_types) = self.runtime_types.get(&previous).cloned() {
+            self.runtime_types.insert(spec, runtime_types);
+        }
+        self.types.insert(spec, new_types);
+        self.insert_version(SubstrateMetadata { version, metadata });
+        let added = self.added_module_types(spec, &report.diff);
+        report.needs_attention.extend(added);
+        report
+    }
+
+    /// types used by the modules added in version `spec` which do not resolve
+    fn added_module_types(
+        &self, spec: SpecVersion, diff: &MetadataDiff,
+    ) -> Vec<MigratedType> {
+        let metadata = self
+            .version_metadata(spec)
+            .expect("version was just registered; qed");
+        let mut unresolved = Vec::new();
+        for change in &diff.changes {
+            if let Change::ModuleAdded { module } = change {
+                if let Ok(module) = metadata.module(module) {
+                    self.verify_module(spec, &module, &mut unresolved);
+                }
+            }
+        }
+
+        let mut types: Vec<MigratedType> = Vec::new();
+        for unresolved in unresolved {
+            let module = unresolved.module.unwrap_or_default();
+            let migrated = MigratedType::new(module, unresolved.ty);
+            if !types.contains(&migrated) {
+                types.push(migrated);
+            }
+        }
+        types
+    }
+}
+
+/// new name of a module, if it was renamed
+fn renamed<'a>(diff: &'a MetadataDiff, module: &'a str) -> &'a str {
+    diff.changes
+        .iter()
+        .find_map(|c| match c {
+            Change::ModuleRenamed { from, to } if from == module => Some(to.as_str()),
+            _ => None,
+        })
+        .unwrap_or(module)
+}
+
+/// the module did not change in a way that may affect its types
+#[cfg(feature = "type-metadata")]
+fn unchanged(diff: &MetadataDiff, module: &str) -> bool {
+    !diff.module_changes(module).any(|c| c.affects_types())
+}
+
+/// all signatures which mention the type `ty`
+fn mentioned_by<'a>(ty: &str, signatures: &'a [String]) -> Vec<&'a String> {
+    signatures
+        .iter()
+        .filter(|s| mentions(&normalize(s), ty))
+        .collect()
+}
+
+/// `signature` contains `ty` as a whole type name,
+/// IE: `T::Balance` is mentioned by `Compact<T::Balance>`
+/// but not by `T::BalanceOf`
+fn mentions(signature: &str, ty: &str) -> bool {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == ':';
+    signature.match_indices(ty).any(|(i, _)| {
+        let before = signature[.. i].chars().next_back();
+        let after = signature[i + ty.len() ..].chars().next();
+        !before.map_or(false, is_ident) && !after.map_or(false, is_ident)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::{RustTypeMarker, EXTRINSIC_VERSION},
+        metadata::test_suite as meta_test_suite,
+        test_suite,
+    };
+    use codec::Encode;
+
+    #[test]
+    fn should_find_whole_type_names() {
+        assert!(mentions(
+            "call transfer(value: Compact<T::Balance>)",
+            "T::Balance"
+        ));
+        assert!(!mentions(
+            "call transfer(value: T::BalanceOf)",
+            "T::Balance"
+        ));
+        assert!(!mentions("call transfer(value: T::Balance)", "Balance"));
+    }
+
+    #[test]
+    fn should_inherit_unchanged_types() {
+        let mut decoder = Decoder::new();
+        let old_version = test_suite::mock_runtime(0);
+        let metadata = meta_test_suite::test_metadata();
+        decoder.register_version(metadata.clone(), old_version.clone());
+        for module in &["TestModule0", "TestModule1"] {
+            decoder
+                .register_marker(&old_version, *module, "T::Moment", RustTypeMarker::U32)
+                .unwrap();
+        }
+        decoder
+            .register_runtime_marker(
+                &old_version,
+                "Address",
+                RustTypeMarker::GenericAddress,
+            )
+            .unwrap();
+
+        let metadata = meta_test_suite::with_call_argument(
+            &metadata,
+            "TestModule1",
+            "TestCall0",
+            "Compact<T::Moment>",
+        );
+        let report =
+            decoder.register_version_migrating(metadata, test_suite::mock_runtime(1));
+
+        assert_eq!(report.from, Some(0));
+        assert_eq!(
+            report.inherited,
+            vec![MigratedType::new("TestModule0", "T::Moment")]
+        );
+        assert_eq!(
+            report.needs_attention,
+            vec![MigratedType::new("TestModule1", "T::Moment")]
+        );
+        // TestModule0::TestCall0(1337)
+        let mut ext = vec![EXTRINSIC_VERSION, 0, 0];
+        ext.extend(1337u32.encode());
+        assert!(decoder.decode_extrinsic(1, &ext.encode()).is_ok());
+        assert!(decoder.runtime_types[&1].contains_key("Address"));
+    }
+
+    #[test]
+    fn should_report_types_of_added_modules() {
+        let mut decoder = Decoder::new();
+        let old_version = test_suite::mock_runtime(0);
+        let metadata = meta_test_suite::test_metadata();
+        decoder.register_version(
+            meta_test_suite::without_module(&metadata, "TestModule2"),
+            old_version.clone(),
+        );
+        decoder
+            .register_runtime_marker(&old_version, "T::Moment", RustTypeMarker::U32)
+            .unwrap();
+
+        let report =
+            decoder.register_version_migrating(metadata, test_suite::mock_runtime(1));
+        assert!(report.diff.changes.contains(&Change::ModuleAdded {
+            module: "TestModule2".to_string()
+        }));
+        assert!(!report.needs_attention.is_empty());
+        for ty in &report.needs_attention {
+            assert_eq!(ty.module, "TestModule2");
+            assert_ne!(ty.ty, "T::Moment");
+        }
+    }
+
+    #[test]
+    fn should_keep_registered_versions() {
+        let mut decoder = test_suite::decoder_with_versions(2);
+        for spec in 0 .. 2 {
+            let version = test_suite::mock_runtime(spec);
+            let marker = if spec == 0 {
+                RustTypeMarker::U32
+            } else {
+                RustTypeMarker::U64
+            };
+            decoder
+                .register_marker(&version, "TestModule0", "T::Moment", marker)
+                .unwrap();
+        }
+
+        let report = decoder.register_version_migrating(
+            meta_test_suite::test_metadata(),
+            test_suite::mock_runtime(1),
+        );
+        assert_eq!(report, MigrationReport::default());
+        assert_eq!(
+            decoder
+                .resolve(1, Some("TestModule0"), "T::Moment")
+                .unwrap(),
+            &RustTypeMarker::U64
+        );
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Spellings of type names, and aliases between them
+//!
+//! The metadata refers to the same type in different ways, IE:
+//! `T::AccountId`, `<T as Trait>::AccountId` or `AccountId`. Type names are
+//! normalized when they are registered as well as when they are looked up:
+//! whitespace which does not separate two words is removed, and associated
+//! types of `T` are written `T::AccountId`.
+//!
+//! A name is looked up in a module as it is, then with or without the `T::`
+//! prefix, then among the aliases of the module and the global aliases.
+//! Names which a module does not define fall back to the runtime-wide
+//! definitions of its version.
+
+use super::{Decoder, RustTypeMarker, SpecVersion};
+use crate::prelude::*;
+use alloc::borrow::Cow;
+
+/// Alternative names of types, which apply to every spec version
+#[derive(Debug, Default)]
+pub(super) struct Aliases {
+    /// aliases used by every module, and the runtime as a whole
+    pub(super) global: HashMap<String, Rc<RustTypeMarker>>,
+    /// aliases specific to a module, by module name
+    pub(super) modules: HashMap<String, HashMap<String, Rc<RustTypeMarker>>>,
+}
+
+impl Decoder {
+    /// Register `alias` as another name of `type_name` within a module,
+    /// for every runtime version
+    ///
+    /// IE: `Balance` for `T::Balance` in `Balances`
+    pub fn register_alias<S>(&mut self, module: S, alias: S, type_name: S)
+    where
+        S: Into<String>,
+    {
+        let type_name = self.intern(RustTypeMarker::TypeName(type_name.into()));
+        self.aliases
+            .modules
+            .entry(module.into())
+            .or_insert_with(HashMap::new)
+            .insert(normalize(&alias.into()).into_owned(), type_name);
+    }
+
+    /// Register `alias` as another name of `type_name` in every module, as
+    /// well as in the runtime as a whole, for every runtime version
+    ///
+    /// Aliases of a module take precedence over global ones
+    pub fn register_global_alias<S>(&mut self, alias: S, type_name: S)
+    where
+        S: Into<String>,
+    {
+        let type_name = self.intern(RustTypeMarker::TypeName(type_name.into()));
+        self.aliases
+            .global
+            .insert(normalize(&alias.into()).into_owned(), type_name);
+    }
+
+    /// the definition or alias registered for `name`, which must be
+    /// normalized, without following aliases
+    pub(super) fn lookup(
+        &self, spec: SpecVersion, module: Option<&str>, name: &str,
+    ) -> Option<&RustTypeMarker> {
+        let spellings = spellings(name);
+        let module_types = module
+            .and_then(|module| self.types.get(&spec)?.get(module))
+            .map(|entry| &entry.markers);
+        let module_aliases = module.and_then(|module| self.aliases.modules.get(module));
+        find(module_types, &spellings)
+            .or_else(|| find(module_aliases, &spellings))
+            .or_else(|| find(Some(&self.aliases.global), &spellings))
+            .or_else(|| find(self.runtime_types.get(&spec), &spellings))
+    }
+}
+
+/// Normalize the spelling of a type name
+///
+/// IE: `Vec< <T as Trait<I>>::Balance >` is normalized to `Vec<T::Balance>`
+pub(super) fn normalize(name: &str) -> Cow<str> {
+    if !name.contains(char::is_whitespace) {
+        return Cow::Borrowed(name);
+    }
+
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut normalized = String::with_capacity(name.len());
+    let mut space = false;
+    for c in name.trim().chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && is_word(c) && normalized.chars().next_back().map_or(false, is_word) {
+            normalized.push(' ');
+        }
+        space = false;
+        normalized.push(c);
+    }
+
+    // `<T as Trait>::` and `<T as Trait<I>>::` are the same as `T::`
+    while let Some(start) = normalized.find("<T as ") {
+        let mut depth = 0;
+        let end = normalized[start ..].char_indices().find_map(|(i, c)| {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                Some(start + i)
+            } else {
+                None
+            }
+        });
+        match end {
+            Some(end) if normalized[end + 1 ..].starts_with("::") => {
+                normalized.replace_range(start ..= end, "T");
+            }
+            _ => break,
+        }
+    }
+    Cow::Owned(normalized)
+}
+
+/// the first of `spellings` defined in `types`
+///
+/// an alias of another spelling of the same name is not a definition, IE:
+/// `T::Balance` defined as `Balance` falls back to a runtime-wide `Balance`
+fn find<'a>(
+    types: Option<&'a HashMap<String, Rc<RustTypeMarker>>>, spellings: &[Cow<str>],
+) -> Option<&'a RustTypeMarker> {
+    let types = types?;
+    spellings
+        .iter()
+        .filter_map(|s| types.get(s.as_ref()))
+        .map(|marker| &**marker)
+        .find(|marker| match marker {
+            RustTypeMarker::TypeName(alias) => {
+                let alias = normalize(alias);
+                !spellings.iter().any(|s| *s == alias)
+            }
+            _ => true,
+        })
+}
+
+/// the spellings `name` may be registered with, IE: `T::AccountId` and
+/// `AccountId`
+fn spellings(name: &str) -> Vec<Cow<str>> {
+    let is_ident = |s: &str| {
+        !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    let mut spellings = vec![Cow::Borrowed(name)];
+    if name.starts_with("T::") && is_ident(&name[3 ..]) {
+        spellings.push(Cow::Borrowed(&name[3 ..]));
+    } else if is_ident(name) {
+        spellings.push(Cow::Owned(format!("T::{}", name)));
+    }
+    spellings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metadata::test_suite as meta_test_suite, test_suite};
+
+    #[test]
+    fn should_normalize_names() {
+        assert_eq!(normalize("T::AccountId"), "T::AccountId");
+        assert_eq!(normalize(" T::AccountId "), "T::AccountId");
+        assert_eq!(normalize("<T as Trait>::AccountId"), "T::AccountId");
+        assert_eq!(
+            normalize("Vec< (<T as Trait<I>>::Balance, T::BlockNumber) >"),
+            "Vec<(T::Balance,T::BlockNumber)>"
+        );
+        assert_eq!(
+            normalize("<T::Lookup as StaticLookup>::Source"),
+            "<T::Lookup as StaticLookup>::Source"
+        );
+        assert_eq!(normalize("[u8; 8]"), "[u8;8]");
+    }
+
+    #[test]
+    fn should_resolve_every_spelling() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        decoder
+            .register_marker(&version, "TestModule0", "T::Moment", RustTypeMarker::U32)
+            .unwrap();
+        decoder
+            .register_runtime_marker(&version, "Balance", RustTypeMarker::U128)
+            .unwrap();
+        decoder
+            .register_marker(
+                &version,
+                "TestModule0",
+                "T::Balance",
+                RustTypeMarker::TypeName("Balance".into()),
+            )
+            .unwrap();
+        decoder.register_alias("TestModule0", "Duration", "Moment");
+        decoder.register_global_alias("Fee", "Balance");
+
+        let module = Some("TestModule0");
+        let moments = ["T::Moment", "<T as Trait>::Moment", " T::Moment ", "Moment"];
+        for name in moments.iter().chain(&["Duration"]) {
+            assert_eq!(decoder.resolve(0, module, name).unwrap(), &RustTypeMarker::U32);
+        }
+        // module-local names fall back to runtime-wide definitions
+        for name in &["Balance", "T::Balance", "Fee"] {
+            assert_eq!(decoder.resolve(0, module, name).unwrap(), &RustTypeMarker::U128);
+        }
+        assert_eq!(decoder.resolve(0, None, "Fee").unwrap(), &RustTypeMarker::U128);
+        assert!(decoder.resolve(0, Some("TestModule1"), "Duration").is_err());
+        assert!(decoder.resolve(0, None, "Moment").is_err());
+    }
+}
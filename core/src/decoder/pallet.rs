@@ -0,0 +1,117 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Register every associated type of a pallet at once
+
+/// Declare a function registering the associated types of a pallet `Trait`
+///
+/// The associated types are listed once per pallet. The function registers
+/// the structure of each of them, as described by `TypeMarker`, for any
+/// runtime implementing the trait, under the name the metadata uses for it
+/// (IE: `T::BlockNumber`).
+///
+/// ```ignore
+/// desub_core::pallet_types! {
+///     /// register the associated types of `frame_system::Trait`
+///     pub fn system_types<T: frame_system::Trait> {
+///         Index, BlockNumber, AccountId,
+///     }
+/// }
+///
+/// system_types::<Runtime>(&mut decoder, &version, "System")?;
+/// ```
+///
+/// The declared function fails if `module` is not part of the metadata of
+/// `version`, like `Decoder::register_marker`
+#[macro_export]
+macro_rules! pallet_types {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident<T: $pallet:path> { $($assoc:ident),* $(,)? }
+    ) => {
+        $(#[$attr])*
+        $vis fn $name<T>(
+            decoder: &mut $crate::decoder::Decoder, version: &$crate::RuntimeVersion,
+            module: &str,
+        ) -> ::core::result::Result<(), $crate::Error>
+        where
+            T: $pallet,
+            $(<T as $pallet>::$assoc: $crate::decoder::TypeMarker,)*
+        {
+            $(
+                decoder.register_marker(
+                    version,
+                    module,
+                    concat!("T::", stringify!($assoc)),
+                    <<T as $pallet>::$assoc as $crate::decoder::TypeMarker>
+                        ::type_marker(),
+                )?;
+            )*
+            ::core::result::Result::Ok(())
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        decoder::{Decoder, RustTypeMarker, SubstrateType},
+        metadata::test_suite as meta_test_suite,
+        test_suite,
+    };
+    use codec::Encode;
+
+    trait TestPallet {
+        type Moment;
+        type Balance;
+    }
+
+    struct TestRuntime;
+    impl TestPallet for TestRuntime {
+        type Moment = u32;
+        type Balance = u128;
+    }
+
+    pallet_types! {
+        /// the associated types of `TestPallet`
+        fn test_pallet_types<T: TestPallet> { Moment, Balance }
+    }
+
+    #[test]
+    fn should_register_pallet_types() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        for module in &["TestModule0", "TestModule1", "TestModule2"] {
+            test_pallet_types::<TestRuntime>(&mut decoder, &version, module).unwrap();
+            assert_eq!(
+                decoder.resolve(0, Some(*module), "T::Moment").unwrap(),
+                &RustTypeMarker::U32
+            );
+            assert_eq!(
+                decoder.resolve(0, Some(*module), "T::Balance").unwrap(),
+                &RustTypeMarker::U128
+            );
+        }
+
+        let value = decoder
+            .decode_storage(0, "TestModule0", "TestStorage0", &1337u32.encode())
+            .unwrap();
+        assert_eq!(value, SubstrateType::U32(1337));
+        assert!(test_pallet_types::<TestRuntime>(&mut decoder, &version, "NotAModule")
+            .is_err());
+    }
+}
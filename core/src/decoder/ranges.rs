@@ -0,0 +1,303 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Register type definitions for a range of runtime versions
+//!
+//! Most types of a chain keep their definition across many runtime
+//! upgrades. A definition registered for a range of spec versions applies to
+//! every version in the range which is registered, whether it is registered
+//! before or after the definition. Modules which are not part of the metadata
+//! of a version are skipped for that version. When definitions overlap, the
+//! one registered last takes precedence.
+//!
+//! A global definition is a runtime-wide one, which modules fall back to for
+//! the types they do not define
+
+use super::{names::normalize, Decoder, RustTypeMarker, SpecVersion};
+use crate::prelude::*;
+use core::ops::{Bound, RangeBounds};
+use serde::{Deserialize, Serialize};
+
+/// A range of spec versions, both ends inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecRange {
+    /// first spec version of the range
+    #[serde(default)]
+    pub from: SpecVersion,
+    /// last spec version of the range, `None` if the range is open ended
+    #[serde(default)]
+    pub to: Option<SpecVersion>,
+}
+
+impl SpecRange {
+    /// create a range from any range of spec versions, IE: `1019..=1031`
+    pub fn new<R: RangeBounds<SpecVersion>>(range: R) -> Self {
+        let from = match range.start_bound() {
+            Bound::Included(from) => *from,
+            Bound::Excluded(from) => from.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let to = match range.end_bound() {
+            // an empty range
+            Bound::Excluded(&0) => {
+                return SpecRange {
+                    from: 1,
+                    to: Some(0),
+                }
+            }
+            Bound::Excluded(to) => Some(to - 1),
+            Bound::Included(to) => Some(*to),
+            Bound::Unbounded => None,
+        };
+        SpecRange { from, to }
+    }
+
+    /// every spec version
+    pub fn all() -> Self {
+        SpecRange { from: 0, to: None }
+    }
+
+    /// whether `spec` is part of the range
+    pub fn contains(&self, spec: SpecVersion) -> bool {
+        spec >= self.from && self.to.map_or(true, |to| spec <= to)
+    }
+}
+
+impl RangeBounds<SpecVersion> for SpecRange {
+    fn start_bound(&self) -> Bound<&SpecVersion> {
+        Bound::Included(&self.from)
+    }
+
+    fn end_bound(&self) -> Bound<&SpecVersion> {
+        self.to.as_ref().map_or(Bound::Unbounded, Bound::Included)
+    }
+}
+
+/// A definition registered for a range of spec versions
+#[derive(Debug, Clone)]
+pub(super) struct RangedType {
+    pub(super) specs: SpecRange,
+    pub(super) scope: Scope,
+    pub(super) name: String,
+    pub(super) marker: Rc<RustTypeMarker>,
+}
+
+/// where a definition applies
+#[derive(Debug, Clone)]
+pub(super) enum Scope {
+    /// the runtime as a whole
+    Runtime,
+    Module(String),
+}
+
+impl Decoder {
+    /// Register the structure of a type used by a module for every
+    /// runtime version in `specs`
+    ///
+    /// Versions registered later on which are part of `specs` get the
+    /// definition as well
+    pub fn register_marker_range<R, S>(
+        &mut self, specs: R, module: S, type_name: S, marker: RustTypeMarker,
+    ) where
+        R: RangeBounds<SpecVersion>,
+        S: Into<String>,
+    {
+        let marker = self.intern(marker);
+        self.add_ranged_type(RangedType {
+            specs: SpecRange::new(specs),
+            scope: Scope::Module(module.into()),
+            name: type_name.into(),
+            marker,
+        });
+    }
+
+    /// Register the structure of a runtime-wide type for every
+    /// runtime version in `specs`
+    ///
+    /// Versions registered later on which are part of `specs` get the
+    /// definition as well
+    pub fn register_runtime_marker_range<R, S>(
+        &mut self, specs: R, type_name: S, marker: RustTypeMarker,
+    ) where
+        R: RangeBounds<SpecVersion>,
+        S: Into<String>,
+    {
+        let marker = self.intern(marker);
+        self.add_ranged_type(RangedType {
+            specs: SpecRange::new(specs),
+            scope: Scope::Runtime,
+            name: type_name.into(),
+            marker,
+        });
+    }
+
+    fn add_ranged_type(&mut self, mut ranged: RangedType) {
+        ranged.name = normalize(&ranged.name).into_owned();
+        let specs = self
+            .versions
+            .iter()
+            .map(|v| v.version.spec_version)
+            .filter(|spec| ranged.specs.contains(*spec))
+            .collect::<Vec<_>>();
+        for spec in specs {
+            self.apply_ranged_type(spec, &ranged);
+        }
+        self.ranged_types.push(ranged);
+    }
+
+    /// apply every ranged definition containing `spec`, in the order they
+    /// were registered
+    pub(super) fn apply_ranged_types(&mut self, spec: SpecVersion) {
+        let ranged = self
+            .ranged_types
+            .iter()
+            .filter(|r| r.specs.contains(spec))
+            .cloned()
+            .collect::<Vec<_>>();
+        for ranged in &ranged {
+            self.apply_ranged_type(spec, ranged);
+        }
+    }
+
+    fn apply_ranged_type(&mut self, spec: SpecVersion, ranged: &RangedType) {
+        match &ranged.scope {
+            Scope::Runtime => {
+                self.runtime_types
+                    .entry(spec)
+                    .or_insert_with(HashMap::new)
+                    .insert(ranged.name.clone(), ranged.marker.clone());
+            }
+            Scope::Module(module) => {
+                // the module is not part of every version in the range
+                if let Ok(entry) = self.entry_mut(spec, module.as_str()) {
+                    entry
+                        .markers
+                        .insert(ranged.name.clone(), ranged.marker.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{metadata::test_suite as meta_test_suite, test_suite};
+
+    #[test]
+    fn should_convert_ranges() {
+        assert_eq!(SpecRange::new(..), SpecRange::all());
+        assert_eq!(
+            SpecRange::new(3 .. 5),
+            SpecRange {
+                from: 3,
+                to: Some(4)
+            }
+        );
+        assert_eq!(
+            SpecRange::new(3 ..= 5),
+            SpecRange {
+                from: 3,
+                to: Some(5)
+            }
+        );
+        assert!(SpecRange::new(7 ..).contains(7));
+        assert!(!SpecRange::new(7 ..).contains(6));
+        assert!(!SpecRange::new(.. 0).contains(0));
+        assert_eq!(SpecRange::new(SpecRange::new(2 ..)), SpecRange::new(2 ..));
+    }
+
+    #[test]
+    fn should_apply_ranges_to_registered_versions() {
+        let mut decoder = test_suite::decoder_with_versions(2);
+        decoder.register_marker_range(
+            .. 1,
+            "TestModule0",
+            "T::Moment",
+            RustTypeMarker::U32,
+        );
+        decoder.register_marker_range(
+            1 ..,
+            "TestModule0",
+            "T::Moment",
+            RustTypeMarker::U64,
+        );
+        decoder.register_marker_range(.., "NotAModule", "T::Moment", RustTypeMarker::U8);
+        decoder.register_runtime_marker_range(
+            ..,
+            "Address",
+            RustTypeMarker::GenericAddress,
+        );
+        decoder.register_runtime_marker_range(1 .., "Balance", RustTypeMarker::U128);
+
+        // a version registered after the definitions
+        decoder.register_version(
+            meta_test_suite::test_metadata(),
+            test_suite::mock_runtime(2),
+        );
+
+        let moment = |spec| decoder.resolve(spec, Some("TestModule0"), "T::Moment");
+        assert_eq!(moment(0).unwrap(), &RustTypeMarker::U32);
+        assert_eq!(moment(1).unwrap(), &RustTypeMarker::U64);
+        assert_eq!(moment(2).unwrap(), &RustTypeMarker::U64);
+        assert_eq!(
+            decoder.resolve(2, None, "Address").unwrap(),
+            &RustTypeMarker::GenericAddress
+        );
+        for module in &[Some("TestModule1"), Some("TestModule2"), None] {
+            assert!(decoder.resolve(0, *module, "Balance").is_err());
+            assert_eq!(
+                decoder.resolve(2, *module, "Balance").unwrap(),
+                &RustTypeMarker::U128
+            );
+        }
+    }
+
+    #[test]
+    fn should_prefer_later_registrations() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        decoder.register_marker_range(
+            ..,
+            "TestModule0",
+            "T::Moment",
+            RustTypeMarker::U32,
+        );
+        decoder
+            .register_marker(&version, "TestModule0", "T::Moment", RustTypeMarker::U64)
+            .unwrap();
+        assert_eq!(
+            decoder
+                .resolve(0, Some("TestModule0"), "T::Moment")
+                .unwrap(),
+            &RustTypeMarker::U64
+        );
+
+        decoder.register_marker_range(
+            0 ..= 0,
+            "TestModule0",
+            "T::Moment",
+            RustTypeMarker::U8,
+        );
+        assert_eq!(
+            decoder
+                .resolve(0, Some("TestModule0"), "T::Moment")
+                .unwrap(),
+            &RustTypeMarker::U8
+        );
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A serializable copy of a `Decoder`
+//!
+//! A snapshot holds the metadata of every registered runtime version,
+//! re-encoded in the latest layout as hex, along with the structural
+//! definitions registered for modules and the runtime as a whole, and the
+//! aliases of type names. Definitions registered for a range of versions are
+//! kept as well, so that versions registered after restoring get them. It is
+//! all that is needed to decode extrinsics, events and storage, so a decoder
+//! built on a backend may be shipped as JSON to other applications.
+//!
+//! Types registered through `type-metadata` are not part of a snapshot
+
+use super::{
+    names::normalize,
+    ranges::{RangedType, Scope},
+    Decoder, RustTypeMarker, SpecRange, SubstrateMetadata,
+};
+use crate::{error::Error, metadata::Metadata, prelude::*, RuntimeVersion};
+use alloc::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Everything registered in a `Decoder`, in a serializable form
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DecoderSnapshot {
+    /// registered runtime versions, by increasing spec version
+    pub versions: Vec<VersionSnapshot>,
+    /// aliases of type names, which apply to every version
+    #[serde(default)]
+    pub aliases: AliasesSnapshot,
+    /// definitions registered for a range of versions, in the order they
+    /// were registered
+    #[serde(default)]
+    pub ranged_types: Vec<RangedTypeSnapshot>,
+}
+
+/// One registered runtime version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionSnapshot {
+    pub version: RuntimeVersion,
+    /// `0x`-prefixed hex of the metadata, in the latest layout
+    pub metadata: String,
+    /// definitions of runtime-wide types, by name
+    #[serde(default)]
+    pub runtime_types: BTreeMap<String, RustTypeMarker>,
+    /// definitions of the types of every module, by module and type name
+    #[serde(default)]
+    pub modules: BTreeMap<String, BTreeMap<String, RustTypeMarker>>,
+}
+
+/// Aliases of type names, by alias
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AliasesSnapshot {
+    /// aliases used by every module, and the runtime as a whole
+    #[serde(default)]
+    pub global: BTreeMap<String, String>,
+    /// aliases specific to a module, by module name
+    #[serde(default)]
+    pub modules: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// A definition registered for a range of spec versions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangedTypeSnapshot {
+    pub specs: SpecRange,
+    /// module the definition applies to, `None` for a runtime-wide definition
+    #[serde(default)]
+    pub module: Option<String>,
+    pub name: String,
+    pub marker: RustTypeMarker,
+}
+
+impl Decoder {
+    /// copy everything needed to decode into a serializable snapshot
+    pub fn to_snapshot(&self) -> DecoderSnapshot {
+        let versions = self
+            .versions
+            .iter()
+            .map(|v| {
+                let spec = v.version.spec_version;
+                let runtime_types = self
+                    .runtime_types
+                    .get(&spec)
+                    .map(|types| {
+                        types.iter().map(|(k, v)| (k.clone(), (**v).clone())).collect()
+                    })
+                    .unwrap_or_default();
+                let modules = self
+                    .types
+                    .get(&spec)
+                    .map(|modules| {
+                        modules
+                            .iter()
+                            .map(|(name, entry)| {
+                                let markers = entry
+                                    .markers
+                                    .iter()
+                                    .map(|(k, v)| (k.clone(), (**v).clone()))
+                                    .collect();
+                                (name.clone(), markers)
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                VersionSnapshot {
+                    version: v.version.clone(),
+                    metadata: format!("0x{}", hex::encode(v.metadata.encode_latest())),
+                    runtime_types,
+                    modules,
+                }
+            })
+            .collect();
+        let aliases = AliasesSnapshot {
+            global: alias_targets(&self.aliases.global),
+            modules: self
+                .aliases
+                .modules
+                .iter()
+                .map(|(module, aliases)| (module.clone(), alias_targets(aliases)))
+                .collect(),
+        };
+        let ranged_types = self
+            .ranged_types
+            .iter()
+            .map(|ranged| RangedTypeSnapshot {
+                specs: ranged.specs,
+                module: match &ranged.scope {
+                    Scope::Runtime => None,
+                    Scope::Module(module) => Some(module.clone()),
+                },
+                name: ranged.name.clone(),
+                marker: (*ranged.marker).clone(),
+            })
+            .collect();
+        DecoderSnapshot {
+            versions,
+            aliases,
+            ranged_types,
+        }
+    }
+
+    /// rebuild a decoder from a snapshot
+    pub fn from_snapshot(snapshot: DecoderSnapshot) -> Result<Self, Error> {
+        let mut decoder = Decoder::new();
+        for version in snapshot.versions {
+            let metadata = Metadata::from_hex(&version.metadata)
+                .map_err(|e| Error::InvalidSnapshot(e.to_string()))?;
+            decoder.insert_version(SubstrateMetadata {
+                version: version.version.clone(),
+                metadata,
+            });
+            for (name, marker) in version.runtime_types {
+                decoder.register_runtime_marker(&version.version, name, marker)?;
+            }
+            for (module, markers) in version.modules {
+                for (name, marker) in markers {
+                    decoder.register_marker(
+                        &version.version,
+                        module.clone(),
+                        name,
+                        marker,
+                    )?;
+                }
+            }
+        }
+        for (alias, type_name) in snapshot.aliases.global {
+            decoder.register_global_alias(alias, type_name);
+        }
+        for (module, aliases) in snapshot.aliases.modules {
+            for (alias, type_name) in aliases {
+                decoder.register_alias(module.clone(), alias, type_name);
+            }
+        }
+        // the versions of the snapshot already hold their ranged
+        // definitions, which may have been overridden since
+        for ranged in snapshot.ranged_types {
+            let marker = decoder.intern(ranged.marker);
+            decoder.ranged_types.push(RangedType {
+                specs: ranged.specs,
+                scope: ranged.module.map_or(Scope::Runtime, Scope::Module),
+                name: normalize(&ranged.name).into_owned(),
+                marker,
+            });
+        }
+        Ok(decoder)
+    }
+
+    /// serialize everything needed to decode to JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.to_snapshot()).expect("snapshots serialize; qed")
+    }
+
+    /// rebuild a decoder from JSON created with `to_json`
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let snapshot = serde_json::from_str(json)
+            .map_err(|e| Error::InvalidSnapshot(e.to_string()))?;
+        Self::from_snapshot(snapshot)
+    }
+}
+
+/// the type name every alias refers to
+fn alias_targets(
+    aliases: &HashMap<String, Rc<RustTypeMarker>>,
+) -> BTreeMap<String, String> {
+    aliases
+        .iter()
+        .filter_map(|(alias, marker)| match &**marker {
+            RustTypeMarker::TypeName(name) => Some((alias.clone(), name.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::SubstrateType, metadata::test_suite as meta_test_suite, test_suite,
+    };
+    use codec::Encode;
+
+    #[test]
+    fn should_restore_decoder_from_json() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        decoder
+            .register_marker(&version, "TestModule0", "T::Moment", RustTypeMarker::U32)
+            .unwrap();
+        decoder
+            .register_runtime_marker(&version, "Address", RustTypeMarker::GenericAddress)
+            .unwrap();
+        decoder.register_alias("TestModule0", "Duration", "T::Moment");
+        decoder.register_global_alias("Source", "Address");
+
+        let restored = Decoder::from_json(&decoder.to_json()).unwrap();
+        assert_eq!(restored.to_snapshot(), decoder.to_snapshot());
+        assert_eq!(restored.to_snapshot().aliases.modules["TestModule0"].len(), 1);
+        let value = restored
+            .decode_storage(0, "TestModule0", "TestStorage0", &1337u32.encode())
+            .unwrap();
+        assert_eq!(value, SubstrateType::U32(1337));
+        assert_eq!(
+            restored.resolve(0, Some("TestModule0"), "Duration").unwrap(),
+            &RustTypeMarker::U32
+        );
+        assert_eq!(
+            restored.resolve(0, None, "Source").unwrap(),
+            &RustTypeMarker::GenericAddress
+        );
+        assert!(Decoder::from_json("{\"versions\": 1}").is_err());
+    }
+
+    #[test]
+    fn should_restore_ranged_types() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        decoder.register_marker_range(
+            ..,
+            "TestModule0",
+            "T::Moment",
+            RustTypeMarker::U32,
+        );
+        decoder.register_runtime_marker_range(1 .., "Address", RustTypeMarker::U8);
+        decoder
+            .register_marker(&version, "TestModule0", "T::Moment", RustTypeMarker::U64)
+            .unwrap();
+
+        let mut restored = Decoder::from_json(&decoder.to_json()).unwrap();
+        assert_eq!(restored.to_snapshot(), decoder.to_snapshot());
+        assert_eq!(
+            restored.resolve(0, Some("TestModule0"), "T::Moment").unwrap(),
+            &RustTypeMarker::U64
+        );
+        restored.register_version(
+            meta_test_suite::test_metadata(),
+            test_suite::mock_runtime(1),
+        );
+        assert_eq!(
+            restored.resolve(1, Some("TestModule0"), "T::Moment").unwrap(),
+            &RustTypeMarker::U32
+        );
+        assert_eq!(restored.resolve(1, None, "Address").unwrap(), &RustTypeMarker::U8);
+    }
+}
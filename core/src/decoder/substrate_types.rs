@@ -0,0 +1,132 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dynamically decoded values
+//!
+//! Every value decoded with a `RustTypeMarker` is represented by a
+//! `SubstrateType`, which serializes to plain JSON:
+//! integers wider than 64 bits and byte arrays are serialized as strings
+//! (decimal and `0x`-prefixed hex respectively), structs as objects, and
+//! enums as an object with the variant name as the only key
+
+use super::extrinsics::GenericCall;
+use crate::{prelude::*, util::to_hex};
+use serde::{
+    ser::{SerializeMap, SerializeSeq},
+    Serialize, Serializer,
+};
+
+/// A value decoded from SCALE without its concrete rust type
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubstrateType {
+    Null,
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    /// a sequence of bytes (`Vec<u8>`, `[u8; N]`)
+    Bytes(Vec<u8>),
+    /// a tuple, array or vector of values
+    Composite(Vec<SubstrateType>),
+    /// a struct with named fields, in the order they were decoded
+    Struct(Vec<(String, SubstrateType)>),
+    Enum {
+        variant: String,
+        value: Box<SubstrateType>,
+    },
+    Option(Option<Box<SubstrateType>>),
+    /// a runtime call nested inside of another type
+    Call(GenericCall),
+}
+
+impl Serialize for SubstrateType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            SubstrateType::Null => serializer.serialize_unit(),
+            SubstrateType::Bool(v) => serializer.serialize_bool(*v),
+            SubstrateType::U8(v) => serializer.serialize_u8(*v),
+            SubstrateType::U16(v) => serializer.serialize_u16(*v),
+            SubstrateType::U32(v) => serializer.serialize_u32(*v),
+            SubstrateType::U64(v) => serializer.serialize_u64(*v),
+            SubstrateType::U128(v) => serializer.serialize_str(&v.to_string()),
+            SubstrateType::I8(v) => serializer.serialize_i8(*v),
+            SubstrateType::I16(v) => serializer.serialize_i16(*v),
+            SubstrateType::I32(v) => serializer.serialize_i32(*v),
+            SubstrateType::I64(v) => serializer.serialize_i64(*v),
+            SubstrateType::I128(v) => serializer.serialize_str(&v.to_string()),
+            SubstrateType::Bytes(v) => serializer.serialize_str(&to_hex(v)),
+            SubstrateType::Composite(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            SubstrateType::Struct(fields) => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for (name, value) in fields {
+                    map.serialize_entry(name, value)?;
+                }
+                map.end()
+            }
+            SubstrateType::Enum { variant, value } => match **value {
+                SubstrateType::Null => serializer.serialize_str(variant),
+                _ => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(variant, value)?;
+                    map.end()
+                }
+            },
+            SubstrateType::Option(v) => match v {
+                Some(v) => serializer.serialize_some(v),
+                None => serializer.serialize_none(),
+            },
+            SubstrateType::Call(call) => call.serialize(serializer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_serialize_to_json() {
+        let value = SubstrateType::Struct(vec![
+            ("free".to_string(), SubstrateType::U128(u128::max_value())),
+            ("id".to_string(), SubstrateType::Bytes(vec![0xde, 0xad])),
+            ("era".to_string(), SubstrateType::Enum {
+                variant: "Immortal".to_string(),
+                value: Box::new(SubstrateType::Null),
+            }),
+            ("tip".to_string(), SubstrateType::Option(None)),
+        ]);
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"free":"340282366920938463463374607431768211455","id":"0xdead","era":"Immortal","tip":null}"#
+        );
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Structural descriptions of SCALE-encoded types
+//!
+//! Substrate metadata only carries the names of types (`T::Balance`,
+//! `Vec<T::AccountId>`, ...), a `RustTypeMarker` describes how the bytes of
+//! such a type are laid out so that they may be decoded without the concrete
+//! rust type
+
+use crate::error::Error;
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use codec::Compact;
+use serde::{Deserialize, Serialize};
+use core::str::FromStr;
+
+/// Describes the shape of a SCALE-encoded type
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RustTypeMarker {
+    /// A type that is only known by its name
+    /// and must be resolved against the definitions registered in the
+    /// decoder
+    TypeName(String),
+    /// A struct with named fields, in the order they are encoded
+    Struct(Vec<StructField>),
+    /// A tuple, or tuple struct
+    Tuple(Vec<RustTypeMarker>),
+    /// An enum; the variant is encoded as a one-byte index
+    Enum(Vec<EnumVariant>),
+    /// An array with a length known at compile time (`[T; N]`)
+    Array { size: usize, ty: Box<RustTypeMarker> },
+    /// A vector prefixed with its compact-encoded length
+    Vec(Box<RustTypeMarker>),
+    Option(Box<RustTypeMarker>),
+    Result(Box<RustTypeMarker>, Box<RustTypeMarker>),
+    /// A compact-encoded unsigned integer
+    Compact(Box<RustTypeMarker>),
+    /// A runtime call, which is decoded with the help of the metadata
+    Call,
+    /// The address format of the indices module
+    /// (either an `AccountId` or a variable-width `AccountIndex`)
+    GenericAddress,
+    /// A mortal or immortal transaction era
+    Era,
+    /// A vector of bits, prefixed with the compact-encoded number of bits
+    /// and packed into bytes
+    BitVec,
+
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Bool,
+    /// The unit type `()`
+    Null,
+}
+
+/// A named field of a struct
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StructField {
+    pub name: String,
+    pub ty: RustTypeMarker,
+}
+
+/// A variant of an enum
+///
+/// Variants without data have a type of `RustTypeMarker::Null`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnumVariant {
+    pub name: String,
+    pub ty: RustTypeMarker,
+}
+
+impl StructField {
+    pub fn new<S: Into<String>>(name: S, ty: RustTypeMarker) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+        }
+    }
+}
+
+impl EnumVariant {
+    pub fn new<S: Into<String>>(name: S, ty: RustTypeMarker) -> Self {
+        Self {
+            name: name.into(),
+            ty,
+        }
+    }
+
+    /// A variant which does not hold any data
+    pub fn unit<S: Into<String>>(name: S) -> Self {
+        Self::new(name, RustTypeMarker::Null)
+    }
+}
+
+impl RustTypeMarker {
+    /// returns true if this marker is an unsigned integer and therefore may be
+    /// compact-encoded
+    pub fn is_unsigned(&self) -> bool {
+        match self {
+            RustTypeMarker::U8
+            | RustTypeMarker::U16
+            | RustTypeMarker::U32
+            | RustTypeMarker::U64
+            | RustTypeMarker::U128 => true,
+            _ => false,
+        }
+    }
+}
+
+/// A rust type which knows the structure of its SCALE encoding
+///
+/// Implemented for primitives, tuples, arrays and the collections SCALE
+/// supports, so that a type a runtime is configured with may be registered
+/// without spelling out its structure
+pub trait TypeMarker {
+    fn type_marker() -> RustTypeMarker;
+}
+
+macro_rules! impl_primitive_markers {
+    ($($ty:ty => $marker:ident),* $(,)?) => {
+        $(
+            impl TypeMarker for $ty {
+                fn type_marker() -> RustTypeMarker {
+                    RustTypeMarker::$marker
+                }
+            }
+        )*
+    };
+}
+
+impl_primitive_markers! {
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    bool => Bool,
+    () => Null,
+}
+
+impl TypeMarker for String {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Vec(Box::new(RustTypeMarker::U8))
+    }
+}
+
+impl<T: TypeMarker> TypeMarker for Vec<T> {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Vec(Box::new(T::type_marker()))
+    }
+}
+
+impl<T: TypeMarker> TypeMarker for Option<T> {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Option(Box::new(T::type_marker()))
+    }
+}
+
+impl<T: TypeMarker> TypeMarker for Box<T> {
+    fn type_marker() -> RustTypeMarker {
+        T::type_marker()
+    }
+}
+
+impl<T: TypeMarker, E: TypeMarker> TypeMarker for Result<T, E> {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Result(Box::new(T::type_marker()), Box::new(E::type_marker()))
+    }
+}
+
+impl<T: TypeMarker> TypeMarker for Compact<T> {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Compact(Box::new(T::type_marker()))
+    }
+}
+
+impl<K: TypeMarker, V: TypeMarker> TypeMarker for BTreeMap<K, V> {
+    fn type_marker() -> RustTypeMarker {
+        RustTypeMarker::Vec(Box::new(<(K, V)>::type_marker()))
+    }
+}
+
+macro_rules! impl_tuple_markers {
+    ($($ty:ident),+) => {
+        impl<$($ty: TypeMarker),+> TypeMarker for ($($ty,)+) {
+            fn type_marker() -> RustTypeMarker {
+                RustTypeMarker::Tuple(vec![$($ty::type_marker()),+])
+            }
+        }
+    };
+}
+
+impl_tuple_markers!(A);
+impl_tuple_markers!(A, B);
+impl_tuple_markers!(A, B, C);
+impl_tuple_markers!(A, B, C, D);
+impl_tuple_markers!(A, B, C, D, E);
+impl_tuple_markers!(A, B, C, D, E, F);
+impl_tuple_markers!(A, B, C, D, E, F, G);
+impl_tuple_markers!(A, B, C, D, E, F, G, H);
+
+macro_rules! impl_array_markers {
+    ($($size:expr),*) => {
+        $(
+            impl<T: TypeMarker> TypeMarker for [T; $size] {
+                fn type_marker() -> RustTypeMarker {
+                    RustTypeMarker::Array {
+                        size: $size,
+                        ty: Box::new(T::type_marker()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_array_markers!(
+    1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23,
+    24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 48, 64, 65, 128, 256
+);
+
+impl FromStr for RustTypeMarker {
+    type Err = Error;
+
+    /// Parse a type as it is written in substrate metadata
+    ///
+    /// Types which are not built-in or cannot be structurally decomposed
+    /// are returned as a `RustTypeMarker::TypeName`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            Err(Error::InvalidType(s.to_string(), "Empty type"))?;
+        }
+
+        if s.starts_with('(') {
+            if !s.ends_with(')') {
+                Err(Error::InvalidType(
+                    s.to_string(),
+                    "Expected closing `)` for tuple",
+                ))?;
+            }
+            let types = split_top_level(&s[1 .. s.len() - 1])
+                .into_iter()
+                .map(|t| t.parse())
+                .collect::<Result<Vec<_>, Error>>()?;
+            if types.is_empty() {
+                return Ok(RustTypeMarker::Null);
+            }
+            return Ok(RustTypeMarker::Tuple(types));
+        }
+
+        if s.starts_with('[') {
+            if !s.ends_with(']') {
+                Err(Error::InvalidType(
+                    s.to_string(),
+                    "Expected closing `]` for array",
+                ))?;
+            }
+            let inner = &s[1 .. s.len() - 1];
+            return match inner.rfind(';') {
+                Some(i) => {
+                    let size = inner[i + 1 ..].trim().parse::<usize>().map_err(|_| {
+                        Error::InvalidType(s.to_string(), "Invalid array length")
+                    })?;
+                    Ok(RustTypeMarker::Array {
+                        size,
+                        ty: Box::new(inner[.. i].parse()?),
+                    })
+                }
+                // a slice, encoded the same as a vector
+                None => Ok(RustTypeMarker::Vec(Box::new(inner.parse()?))),
+            };
+        }
+
+        // a qualified path such as `<T as Trait>::Type`
+        if s.starts_with('<') {
+            return Ok(RustTypeMarker::TypeName(s.to_string()));
+        }
+
+        if let Some(marker) = builtin(s) {
+            return Ok(marker);
+        }
+
+        if let (Some(open), true) = (s.find('<'), s.ends_with('>')) {
+            let name = s[.. open].trim();
+            let params = split_top_level(&s[open + 1 .. s.len() - 1]);
+            let marker = match (name, params.as_slice()) {
+                ("Vec", [ty]) => RustTypeMarker::Vec(Box::new(ty.parse()?)),
+                ("Option", [ty]) => RustTypeMarker::Option(Box::new(ty.parse()?)),
+                ("Compact", [ty]) => RustTypeMarker::Compact(Box::new(ty.parse()?)),
+                ("Box", [ty]) => ty.parse()?,
+                ("Result", [ok, err]) => {
+                    RustTypeMarker::Result(Box::new(ok.parse()?), Box::new(err.parse()?))
+                }
+                ("BTreeMap", [k, v]) => RustTypeMarker::Vec(Box::new(
+                    RustTypeMarker::Tuple(vec![k.parse()?, v.parse()?]),
+                )),
+                _ => RustTypeMarker::TypeName(s.to_string()),
+            };
+            return Ok(marker);
+        }
+
+        Ok(RustTypeMarker::TypeName(s.to_string()))
+    }
+}
+
+/// types which are understood without any registration
+fn builtin(s: &str) -> Option<RustTypeMarker> {
+    let bytes = |size| RustTypeMarker::Array {
+        size,
+        ty: Box::new(RustTypeMarker::U8),
+    };
+    let marker = match s {
+        "u8" => RustTypeMarker::U8,
+        "u16" => RustTypeMarker::U16,
+        "u32" => RustTypeMarker::U32,
+        "u64" => RustTypeMarker::U64,
+        "u128" => RustTypeMarker::U128,
+        "i8" => RustTypeMarker::I8,
+        "i16" => RustTypeMarker::I16,
+        "i32" => RustTypeMarker::I32,
+        "i64" => RustTypeMarker::I64,
+        "i128" => RustTypeMarker::I128,
+        "bool" => RustTypeMarker::Bool,
+        "()" => RustTypeMarker::Null,
+        "Bytes" | "&[u8]" => RustTypeMarker::Vec(Box::new(RustTypeMarker::U8)),
+        "H160" => bytes(20),
+        "H256" => bytes(32),
+        "H512" => bytes(64),
+        "Call" => RustTypeMarker::Call,
+        "GenericAddress" => RustTypeMarker::GenericAddress,
+        "Era" => RustTypeMarker::Era,
+        "BitVec" => RustTypeMarker::BitVec,
+        _ => return None,
+    };
+    Some(marker)
+}
+
+/// split a list of types on the commas which are not nested inside of
+/// generics, tuples or arrays
+pub(crate) fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start .. i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(s[start ..].trim());
+    parts.into_iter().filter(|p| !p.is_empty()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_describe_rust_types() {
+        let parsed = |ty: &str| ty.parse::<RustTypeMarker>().unwrap();
+        assert_eq!(<Vec<(u32, bool)>>::type_marker(), parsed("Vec<(u32, bool)>"));
+        assert_eq!(<Option<[u8; 32]>>::type_marker(), parsed("Option<[u8; 32]>"));
+        assert_eq!(<Compact<u128>>::type_marker(), parsed("Compact<u128>"));
+        assert_eq!(<BTreeMap<u8, ()>>::type_marker(), parsed("BTreeMap<u8, ()>"));
+        assert_eq!(
+            <Result<(), Box<String>>>::type_marker(),
+            parsed("Result<(), Bytes>")
+        );
+    }
+
+    #[test]
+    fn should_parse_type_markers() {
+        let name = |s: &str| RustTypeMarker::TypeName(s.to_string());
+        assert_eq!("u32".parse::<RustTypeMarker>().unwrap(), RustTypeMarker::U32);
+        assert_eq!(
+            "Vec<T::AccountId>".parse::<RustTypeMarker>().unwrap(),
+            RustTypeMarker::Vec(Box::new(name("T::AccountId")))
+        );
+        assert_eq!(
+            "Compact<T::Balance>".parse::<RustTypeMarker>().unwrap(),
+            RustTypeMarker::Compact(Box::new(name("T::Balance")))
+        );
+        assert_eq!(
+            "<T::Lookup as StaticLookup>::Source"
+                .parse::<RustTypeMarker>()
+                .unwrap(),
+            name("<T::Lookup as StaticLookup>::Source")
+        );
+        assert_eq!(
+            "(T::AccountId, Vec<(u32, bool)>)"
+                .parse::<RustTypeMarker>()
+                .unwrap(),
+            RustTypeMarker::Tuple(vec![
+                name("T::AccountId"),
+                RustTypeMarker::Vec(Box::new(RustTypeMarker::Tuple(vec![
+                    RustTypeMarker::U32,
+                    RustTypeMarker::Bool
+                ])))
+            ])
+        );
+        assert_eq!(
+            "[u8; 32]".parse::<RustTypeMarker>().unwrap(),
+            RustTypeMarker::Array {
+                size: 32,
+                ty: Box::new(RustTypeMarker::U8)
+            }
+        );
+        assert_eq!(
+            "Box<<T as Trait>::Call>".parse::<RustTypeMarker>().unwrap(),
+            name("<T as Trait>::Call")
+        );
+    }
+
+    #[test]
+    fn should_not_parse_unclosed_types() {
+        assert!("(u32, u64".parse::<RustTypeMarker>().is_err());
+        assert!("[u8; 32".parse::<RustTypeMarker>().is_err());
+        assert!("[u8; x]".parse::<RustTypeMarker>().is_err());
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Import the type definitions of polkadot-js
+//!
+//! A polkadot-js `typesBundle` holds, for every spec name, type definitions
+//! by range of spec versions (`minmax`) and aliases of type names within a
+//! module. It is converted to `TypeDefinitions` as follows:
+//!
+//! - polkadot-js does not know which module a type belongs to, so every type
+//!   is shared by all modules
+//! - an alias becomes a definition of the module, naming the type it
+//!   stands for. Modules are named by their section (IE: `technicalCommittee`)
+//! - objects are structs, unless they have an `_enum` key (a list of unit
+//!   variants, or an object of variants) or a `_set` key, which is encoded
+//!   as an unsigned integer of `_bitLength` bits
+//! - `Null` and `Text` are understood as `()` and `Bytes`
+
+use super::{
+    Decoder, SpecRange, TypeDefinition, TypeDefinitions, VariantDefinition,
+    VersionDefinitions,
+};
+use crate::{error::Error, prelude::*};
+use alloc::collections::BTreeMap;
+use core::fmt;
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+
+impl TypeDefinitions {
+    /// import the definitions of the runtime named `spec_name` from a
+    /// polkadot-js `typesBundle`
+    pub fn from_types_bundle(json: &str, spec_name: &str) -> Result<Self, Error> {
+        let bundle: Json = serde_json::from_str(json)
+            .map_err(|e| Error::InvalidDefinitions(e.to_string()))?;
+        let spec = bundle
+            .get("spec")
+            .and_then(|specs| specs.get(spec_name))
+            .ok_or_else(|| invalid(format!("no definitions for `{}`", spec_name)))?;
+
+        let mut versions = Vec::new();
+        let types = match spec.get("types") {
+            Some(types) => types.as_array()?,
+            None => &[],
+        };
+        for version in types {
+            let types = match version.get("types") {
+                Some(types) => types
+                    .as_object()?
+                    .iter()
+                    .map(|(name, def)| Ok((name.clone(), definition(def)?)))
+                    .collect::<Result<_, Error>>()?,
+                None => BTreeMap::new(),
+            };
+            versions.push(VersionDefinitions {
+                specs: min_max(version.get("minmax"))?,
+                types,
+                ..Default::default()
+            });
+        }
+
+        // aliases apply to every version, and take precedence over the
+        // shared types
+        if let Some(alias) = spec.get("alias") {
+            let mut modules = BTreeMap::new();
+            for (section, aliases) in alias.as_object()? {
+                let types = aliases
+                    .as_object()?
+                    .iter()
+                    .map(|(name, ty)| {
+                        let ty = TypeDefinition::Type(js_type(ty.as_str()?));
+                        Ok((name.clone(), ty))
+                    })
+                    .collect::<Result<_, Error>>()?;
+                modules.insert(module_name(section), types);
+            }
+            versions.push(VersionDefinitions {
+                modules,
+                ..Default::default()
+            });
+        }
+        Ok(TypeDefinitions { versions })
+    }
+}
+
+impl Decoder {
+    /// Register the definitions of the runtime named `spec_name` from a
+    /// polkadot-js `typesBundle`
+    pub fn register_types_bundle(
+        &mut self, json: &str, spec_name: &str,
+    ) -> Result<(), Error> {
+        self.register_definitions(&TypeDefinitions::from_types_bundle(json, spec_name)?)
+    }
+}
+
+/// the definition of a type of polkadot-js
+fn definition(def: &Json) -> Result<TypeDefinition, Error> {
+    let fields = match def {
+        Json::String(ty) => return Ok(TypeDefinition::Type(js_type(ty))),
+        Json::Object(fields) => fields,
+        _ => return Err(invalid("expected a type or an object")),
+    };
+
+    if let Some(variants) = def.get("_enum") {
+        let variants = match variants {
+            Json::Array(names) => names
+                .iter()
+                .map(|name| Ok(VariantDefinition::Unit(name.as_str()?.to_string())))
+                .collect::<Result<_, Error>>()?,
+            Json::Object(variants) => variants
+                .iter()
+                .map(|(name, def)| {
+                    Ok(VariantDefinition::Data(name.clone(), definition(def)?))
+                })
+                .collect::<Result<_, Error>>()?,
+            _ => return Err(invalid("expected a list or an object of variants")),
+        };
+        return Ok(TypeDefinition::Enum { variants });
+    }
+
+    if let Some(set) = def.get("_set") {
+        let ty = match set.get("_bitLength") {
+            Some(Json::Number(bits)) if *bits == 8.0 => "u8",
+            Some(Json::Number(bits)) if *bits == 16.0 => "u16",
+            Some(Json::Number(bits)) if *bits == 32.0 => "u32",
+            Some(Json::Number(bits)) if *bits == 64.0 => "u64",
+            _ => return Err(invalid("invalid `_bitLength` of a set")),
+        };
+        return Ok(TypeDefinition::Type(ty.to_string()));
+    }
+
+    // keys starting with `_` (IE: `_alias`) describe the struct,
+    // and are not fields
+    let fields = fields
+        .iter()
+        .filter(|(name, _)| !name.starts_with('_'))
+        .map(|(name, def)| Ok((name.clone(), definition(def)?)))
+        .collect::<Result<_, Error>>()?;
+    Ok(TypeDefinition::Struct { fields })
+}
+
+/// an inclusive `[min, max]` range, either of which may be `null`
+fn min_max(range: Option<&Json>) -> Result<SpecRange, Error> {
+    let bound = |bound: Option<&Json>| match bound {
+        None | Some(Json::Null) => Ok(None),
+        // only whole numbers which fit a spec version survive the casts
+        Some(Json::Number(n)) if *n as u32 as f64 == *n => Ok(Some(*n as u32)),
+        _ => Err(invalid("invalid bound of `minmax`")),
+    };
+    let range = match range {
+        Some(range) => range.as_array()?,
+        None => return Ok(SpecRange::all()),
+    };
+    Ok(SpecRange {
+        from: bound(range.get(0))?.unwrap_or(0),
+        to: bound(range.get(1))?,
+    })
+}
+
+/// a type of polkadot-js, as it would be written in the metadata
+fn js_type(ty: &str) -> String {
+    let mut out = String::with_capacity(ty.len());
+    let mut start = None;
+    for (i, c) in ty.char_indices() {
+        let ident = c.is_alphanumeric() || c == '_';
+        match start {
+            Some(s) if !ident => {
+                out.push_str(js_name(&ty[s .. i]));
+                start = None;
+            }
+            None if ident => start = Some(i),
+            _ => (),
+        }
+        if !ident {
+            out.push(c);
+        }
+    }
+    if let Some(s) = start {
+        out.push_str(js_name(&ty[s ..]));
+    }
+    out
+}
+
+fn js_name(name: &str) -> &str {
+    match name {
+        "Null" => "()",
+        "Text" => "Bytes",
+        name => name,
+    }
+}
+
+/// the name of a module from its section in polkadot-js,
+/// IE: `TechnicalCommittee` for `technicalCommittee`
+fn module_name(section: &str) -> String {
+    let mut chars = section.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn invalid<S: Into<String>>(msg: S) -> Error {
+    Error::InvalidDefinitions(msg.into())
+}
+
+/// JSON, keeping the order of the keys of objects,
+/// which is the order of the fields and variants of a type
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    /// booleans are never part of a type definition
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => {
+                entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, Error> {
+        match self {
+            Json::String(s) => Ok(s),
+            _ => Err(invalid("expected a string")),
+        }
+    }
+
+    fn as_array(&self) -> Result<&[Json], Error> {
+        match self {
+            Json::Array(values) => Ok(values),
+            _ => Err(invalid("expected an array")),
+        }
+    }
+
+    fn as_object(&self) -> Result<&[(String, Json)], Error> {
+        match self {
+            Json::Object(entries) => Ok(entries),
+            _ => Err(invalid("expected an object")),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Json {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("JSON")
+    }
+
+    fn visit_unit<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<Json, E> {
+        Ok(Json::Bool)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Json, E> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Json, E> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Json, E> {
+        Ok(Json::Number(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Json, E> {
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(Json::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(Json::Object(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::{EnumVariant, RustTypeMarker, StructField},
+        test_suite,
+    };
+
+    const BUNDLE: &str = r#"{
+        "spec": {
+            "test-runtime": {
+                "alias": { "testModule0": { "Lock": "BalanceLock" } },
+                "types": [
+                    {
+                        "minmax": [0, null],
+                        "types": {
+                            "Moment": "u64",
+                            "Reasons": { "_enum": ["Fee", "Misc", "All"] },
+                            "BalanceLock": {
+                                "id": "[u8; 8]",
+                                "amount": "Balance",
+                                "reasons": "Reasons",
+                                "_alias": { "id": "lock_id" }
+                            },
+                            "Status": { "_enum": { "Free": "Null", "Named": "Text" } },
+                            "Flags": { "_set": { "_bitLength": 16, "A": 1, "B": 2 } }
+                        }
+                    },
+                    { "minmax": [null, 0], "types": { "Balance": "u64" } },
+                    { "minmax": [1, null], "types": { "Balance": "u128" } }
+                ]
+            }
+        }
+    }"#;
+
+    #[test]
+    fn should_import_types_bundle() {
+        let definitions =
+            TypeDefinitions::from_types_bundle(BUNDLE, "test-runtime").unwrap();
+        assert_eq!(definitions.versions.len(), 4);
+        assert_eq!(definitions.versions[1].specs, SpecRange::new(..= 0));
+        assert_eq!(definitions.versions[2].specs, SpecRange::new(1 ..));
+        assert!(definitions.versions[3].modules.contains_key("TestModule0"));
+        assert!(TypeDefinitions::from_types_bundle(BUNDLE, "kusama").is_err());
+
+        let mut decoder = test_suite::decoder_with_versions(2);
+        decoder.register_types_bundle(BUNDLE, "test-runtime").unwrap();
+
+        let module = Some("TestModule0");
+        assert_eq!(
+            decoder.resolve(1, module, "Lock").unwrap(),
+            &RustTypeMarker::Struct(vec![
+                StructField::new(
+                    "id",
+                    RustTypeMarker::Array {
+                        size: 8,
+                        ty: Box::new(RustTypeMarker::U8)
+                    }
+                ),
+                StructField::new("amount", RustTypeMarker::TypeName("Balance".into())),
+                StructField::new("reasons", RustTypeMarker::TypeName("Reasons".into())),
+            ])
+        );
+        assert_eq!(
+            decoder.resolve(1, module, "Status").unwrap(),
+            &RustTypeMarker::Enum(vec![
+                EnumVariant::unit("Free"),
+                EnumVariant::new(
+                    "Named",
+                    RustTypeMarker::Vec(Box::new(RustTypeMarker::U8))
+                ),
+            ])
+        );
+        assert_eq!(decoder.resolve(1, module, "Flags").unwrap(), &RustTypeMarker::U16);
+        assert_eq!(decoder.resolve(0, None, "Balance").unwrap(), &RustTypeMarker::U64);
+        assert_eq!(decoder.resolve(1, None, "Balance").unwrap(), &RustTypeMarker::U128);
+    }
+
+    #[test]
+    fn should_convert_js_types() {
+        assert_eq!(js_type("Vec<(Text, Null)>"), "Vec<(Bytes, ())>");
+        assert_eq!(js_type("Option<TextHash>"), "Option<TextHash>");
+        assert_eq!(module_name("technicalCommittee"), "TechnicalCommittee");
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Check that the registered types cover the metadata
+//!
+//! Every type mentioned by a call, event, constant or the value of a storage
+//! entry must resolve to a structural definition, as well as every type those
+//! definitions refer to. Keys of storage maps are hashed and never decoded,
+//! so they are not checked. The `Events` of the system module are decoded
+//! with the help of the metadata, rather than with a definition
+
+use super::{
+    events::{event_arg_marker, SYSTEM_MODULE},
+    extrinsics::{ADDRESS_TYPE, SIGNATURE_TYPE, SIGNED_EXTRA_TYPE},
+    Decoder, RustTypeMarker, SpecVersion,
+};
+use crate::{metadata::ModuleMetadata, prelude::*};
+use alloc::collections::BTreeSet;
+use serde::Serialize;
+
+/// storage entry of the system module holding the events of a block
+const EVENTS_STORAGE: &str = "Events";
+
+/// A type which is used by the metadata, but cannot be resolved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnresolvedType {
+    pub spec: SpecVersion,
+    /// module the type is used in, `None` for runtime-wide types
+    pub module: Option<String>,
+    /// name of the type, as it appears in the metadata or in a definition
+    pub ty: String,
+    /// the call, event, storage entry or constant using the type,
+    /// IE: `call transfer`
+    pub used_in: String,
+}
+
+impl Decoder {
+    /// Verify that every type used by the metadata of every registered
+    /// version resolves to a definition
+    ///
+    /// Returns every type which could not be resolved
+    pub fn verify(&self) -> Result<(), Vec<UnresolvedType>> {
+        let mut unresolved = Vec::new();
+        for version in &self.versions {
+            let spec = version.version.spec_version;
+            for module in version.metadata.modules() {
+                self.verify_module(spec, module, &mut unresolved);
+            }
+            for name in &[ADDRESS_TYPE, SIGNATURE_TYPE, SIGNED_EXTRA_TYPE] {
+                let ty = RustTypeMarker::TypeName(name.to_string());
+                for missing in self.unresolved(spec, None, &ty) {
+                    unresolved.push(UnresolvedType {
+                        spec,
+                        module: None,
+                        ty: missing,
+                        used_in: "extrinsic".to_string(),
+                    });
+                }
+            }
+        }
+
+        if unresolved.is_empty() {
+            Ok(())
+        } else {
+            Err(unresolved)
+        }
+    }
+
+    pub(super) fn verify_module(
+        &self, spec: SpecVersion, module: &ModuleMetadata,
+        unresolved: &mut Vec<UnresolvedType>,
+    ) {
+        let mut usages = Vec::new();
+        for call in module.calls() {
+            for arg in call.arguments() {
+                usages.push((format!("call {}", call.name()), arg.ty.parse()));
+            }
+        }
+        for event in module.events() {
+            for arg in event.arguments() {
+                usages.push((format!("event {}", event.name), event_arg_marker(&arg)));
+            }
+        }
+        for (name, entry) in module.storage_keys() {
+            if module.name() == SYSTEM_MODULE && name == EVENTS_STORAGE {
+                continue;
+            }
+            usages.push((format!("storage {}", name), entry.value_type().parse()));
+        }
+        for constant in module.constants() {
+            usages.push((format!("constant {}", constant.name), constant.ty.parse()));
+        }
+
+        for (used_in, ty) in usages {
+            let missing = match ty {
+                Ok(ty) => self.unresolved(spec, Some(module.name()), &ty),
+                Err(e) => vec![e.to_string()],
+            };
+            for ty in missing {
+                unresolved.push(UnresolvedType {
+                    spec,
+                    module: Some(module.name().to_string()),
+                    ty,
+                    used_in: used_in.clone(),
+                });
+            }
+        }
+    }
+
+    /// names of all types `ty` refers to which cannot be resolved
+    fn unresolved(
+        &self, spec: SpecVersion, module: Option<&str>, ty: &RustTypeMarker,
+    ) -> Vec<String> {
+        let mut visited = BTreeSet::new();
+        let mut missing = Vec::new();
+        self.collect_unresolved(spec, module, ty, &mut visited, &mut missing);
+        missing
+    }
+
+    fn collect_unresolved(
+        &self, spec: SpecVersion, module: Option<&str>, ty: &RustTypeMarker,
+        visited: &mut BTreeSet<String>, missing: &mut Vec<String>,
+    ) {
+        let children = match ty {
+            RustTypeMarker::TypeName(name) => {
+                if !visited.insert(name.clone()) {
+                    return;
+                }
+                match self.resolve(spec, module, name) {
+                    Ok(resolved) => vec![resolved],
+                    Err(_) => {
+                        missing.push(name.clone());
+                        return;
+                    }
+                }
+            }
+            RustTypeMarker::Struct(fields) => fields.iter().map(|f| &f.ty).collect(),
+            RustTypeMarker::Enum(variants) => variants.iter().map(|v| &v.ty).collect(),
+            RustTypeMarker::Tuple(types) => types.iter().collect(),
+            RustTypeMarker::Array { ty, .. }
+            | RustTypeMarker::Vec(ty)
+            | RustTypeMarker::Option(ty)
+            | RustTypeMarker::Compact(ty) => vec![&**ty],
+            RustTypeMarker::Result(ok, err) => vec![&**ok, &**err],
+            _ => Vec::new(),
+        };
+        for child in children {
+            self.collect_unresolved(spec, module, child, visited, missing);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        decoder::dispatch_error, metadata::test_suite as meta_test_suite, test_suite,
+    };
+
+    #[test]
+    fn should_verify_registered_types() {
+        let mut decoder = Decoder::new();
+        let version = test_suite::mock_runtime(0);
+        decoder.register_version(meta_test_suite::test_metadata(), version.clone());
+        let unresolved = decoder.verify().unwrap_err();
+        assert!(unresolved
+            .iter()
+            .any(|u| u.module.is_none() && u.ty == "Address"));
+        assert!(unresolved.iter().any(|u| {
+            u.module.as_deref() == Some("TestModule0")
+                && u.ty == "T::Moment"
+                && u.used_in == "call TestCall0"
+        }));
+
+        for module in &["TestModule0", "TestModule1", "TestModule2"] {
+            let types = vec![
+                ("T::Moment", RustTypeMarker::U32),
+                ("usize", RustTypeMarker::U64),
+                (
+                    "F::Precision",
+                    RustTypeMarker::TypeName("usize".to_string()),
+                ),
+                ("TestEvent0", RustTypeMarker::U8),
+                ("TestEvent1", RustTypeMarker::U16),
+                ("TestEvent2", RustTypeMarker::Bool),
+            ];
+            for (name, marker) in types {
+                decoder
+                    .register_marker(&version, *module, name, marker)
+                    .unwrap();
+            }
+        }
+        decoder
+            .register_marker(&version, "System", "DispatchInfo", RustTypeMarker::U32)
+            .unwrap();
+        for name in &[ADDRESS_TYPE, SIGNATURE_TYPE, SIGNED_EXTRA_TYPE] {
+            decoder
+                .register_runtime_marker(&version, *name, RustTypeMarker::Null)
+                .unwrap();
+        }
+        let unresolved = decoder.verify().unwrap_err();
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].ty, "DispatchError");
+        assert_eq!(unresolved[0].used_in, "event ExtrinsicFailed");
+
+        decoder
+            .register_marker(&version, "System", "DispatchError", dispatch_error())
+            .unwrap();
+        assert_eq!(decoder.verify(), Ok(()));
+    }
+}
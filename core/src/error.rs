@@ -1,10 +1,33 @@
+use crate::metadata::MetadataError;
 use codec::Error as CodecError;
-use failure::Fail;
+use crate::prelude::*;
 
-#[derive(Debug, Fail)]
+#[derive(Debug, derive_more::Display)]
 pub enum Error {
-    #[fail(display = "Codec {:?}", _0)]
-    Codec(#[fail(cause)] CodecError),
+    #[display(fmt = "Codec {:?}", _0)]
+    Codec(CodecError),
+    #[display(fmt = "Metadata {}", _0)]
+    Metadata(MetadataError),
+    #[display(fmt = "Spec Version {} has not been registered", _0)]
+    SpecVersionNotFound(u32),
+    #[display(fmt = "Type {} not found in {}", _1, _0)]
+    TypeNotFound(String, String),
+    #[display(fmt = "Invalid Type {}: {}", _0, _1)]
+    InvalidType(String, &'static str),
+    #[display(fmt = "Invalid enum index {}", _0)]
+    InvalidEnumIndex(u8),
+    #[display(fmt = "Expected {} bytes, found {}", _0, _1)]
+    InvalidLength(usize, usize),
+    #[display(fmt = "{} bytes left over after decoding", _0)]
+    TrailingBytes(usize),
+    #[display(fmt = "Values nested more than {} levels deep", _0)]
+    TooDeep(usize),
+    #[display(fmt = "Unsupported extrinsic version {}", _0)]
+    UnsupportedExtrinsicVersion(u8),
+    #[display(fmt = "Invalid decoder snapshot: {}", _0)]
+    InvalidSnapshot(String),
+    #[display(fmt = "Invalid type definitions: {}", _0)]
+    InvalidDefinitions(String),
 }
 
 impl From<CodecError> for Error {
@@ -12,3 +35,12 @@ impl From<CodecError> for Error {
         Error::Codec(err)
     }
 }
+
+impl From<MetadataError> for Error {
+    fn from(err: MetadataError) -> Error {
+        Error::Metadata(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,84 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Hashers used for storage keys
+//!
+//! Same output as the hashing functions of `sp-core`
+
+use blake2_rfc::blake2b::blake2b;
+use core::hash::Hasher;
+use twox_hash::XxHash64;
+
+/// 128-bit Blake2b hash
+pub fn blake2_128(data: &[u8]) -> [u8; 16] {
+    let mut r = [0; 16];
+    r.copy_from_slice(blake2b(16, &[], data).as_bytes());
+    r
+}
+
+/// 256-bit Blake2b hash
+pub fn blake2_256(data: &[u8]) -> [u8; 32] {
+    let mut r = [0; 32];
+    r.copy_from_slice(blake2b(32, &[], data).as_bytes());
+    r
+}
+
+/// 64-bit XX hash
+pub fn twox_64(data: &[u8]) -> [u8; 8] {
+    let mut r = [0; 8];
+    twox(data, &mut r);
+    r
+}
+
+/// 128-bit XX hash
+pub fn twox_128(data: &[u8]) -> [u8; 16] {
+    let mut r = [0; 16];
+    twox(data, &mut r);
+    r
+}
+
+/// 256-bit XX hash
+pub fn twox_256(data: &[u8]) -> [u8; 32] {
+    let mut r = [0; 32];
+    twox(data, &mut r);
+    r
+}
+
+/// fill `dest` with 64-bit XX hashes of `data`, seeded with 0, 1, 2...
+fn twox(data: &[u8], dest: &mut [u8]) {
+    for (seed, chunk) in dest.chunks_mut(8).enumerate() {
+        let mut hasher = XxHash64::with_seed(seed as u64);
+        hasher.write(data);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_hash_like_substrate() {
+        assert_eq!(
+            hex::encode(twox_128(b"System")),
+            "26aa394eea5630e07c48ae0c9558cef7"
+        );
+        assert_eq!(
+            hex::encode(blake2_256(b"")),
+            "0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8"
+        );
+    }
+}
@@ -14,16 +14,36 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
+//! Decode SCALE-encoded substrate types without the runtime they are
+//! defined in
+//!
+//! Without the default `std` feature, the `alloc` feature builds the crate
+//! `no_std`, only needing `alloc`.
+//! The optional `type-metadata` feature adds `Decoder::register`, which
+//! registers types deriving `type_metadata::Metadata`
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "alloc")))]
+compile_error!("either the `std` or the `alloc` feature must be enabled");
+
+#[cfg_attr(not(feature = "std"), macro_use)]
+extern crate alloc;
+
 pub mod decoder;
 mod error;
+pub mod hashing;
 #[allow(unused, dead_code)] // TODO: refactor to not need this attribute
 pub mod metadata;
+mod prelude;
+mod runtime_version;
+mod util;
 
-#[cfg(test)]
-mod test_suite;
+pub use error::Error;
+pub use runtime_version::{ApiId, RuntimeVersion};
 
 #[cfg(test)]
-extern crate alloc;
+mod test_suite;
 
 #[cfg(test)]
 mod tests {
@@ -17,6 +17,11 @@
 // taken directly and modified from substrate-subxt:
 // https://github.com/paritytech/substrate-subxt
 
+#[macro_use]
+mod versions;
+mod diff;
+pub mod frame;
+mod serialize;
 #[cfg(test)]
 pub mod test_suite;
 mod version_07;
@@ -24,24 +29,16 @@ mod version_08;
 mod version_09;
 mod version_10;
 mod version_11;
-mod versions;
 
 use codec::{Decode, Encode, EncodeAsRef, HasCompact};
-use codec411::Decode as OldDecode;
-use failure::Fail;
-use runtime_metadata_latest::{
-    DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
-    StorageEntryType, StorageHasher, META_RESERVED,
-};
-
-use std::{
-    collections::{HashMap, HashSet},
-    convert::{TryFrom, TryInto},
-    marker::PhantomData,
-    rc::Rc,
-    str::FromStr,
-};
-use substrate_primitives::storage::StorageKey;
+use serde::Serialize;
+use frame::latest::{StorageEntryModifier, StorageEntryType, StorageHasher};
+
+pub use diff::{Change, MetadataDiff, ModuleIndices};
+pub use versions::{Converters, LatestMetadata, MetadataVersion};
+
+use core::{fmt, marker::PhantomData, str::FromStr};
+use crate::{decoder::split_top_level, hashing, prelude::*};
 
 /// Newtype struct around a Vec<u8> (vector of bytes)
 #[derive(Clone)]
@@ -64,20 +61,76 @@ pub enum MetadataError {
     ModuleNotFound(String),
     CallNotFound(&'static str),
     EventNotFound(u8),
-    StorageNotFound(&'static str),
+    StorageNotFound(String),
+    ConstantNotFound(&'static str),
     StorageTypeError,
     MapValueTypeError,
+    ModuleIndexNotFound(u8),
+    CallIndexNotFound(u8),
+    #[display(fmt = "ErrorNotFound({}, {})", _0, _1)]
+    ErrorNotFound(u8, u8),
 }
 
 #[derive(Clone, Debug, PartialEq)]
 /// Metadata struct encompassing calls, storage, and events
+///
+/// Serializes to JSON with the schema described in the `serialize` module
 pub struct Metadata {
-    /// Hashmap of Modules (name -> module-specific metadata)
-    modules: HashMap<String, Rc<ModuleMetadata>>,
-    modules_by_event_index: HashMap<u8, String>,
+    /// Modules, in the order they are declared in the runtime
+    modules: Vec<Rc<ModuleMetadata>>,
+    /// name of a module -> position in `modules`
+    modules_by_name: HashMap<String, usize>,
+    /// index of a module in the outer `Event` enum -> position in `modules`
+    modules_by_event_index: HashMap<u8, usize>,
+    /// index of a module in the outer `Call` enum -> position in `modules`
+    modules_by_call_index: HashMap<u8, usize>,
+    /// the extrinsic format, only part of V11 metadata onwards
+    extrinsic: Option<ExtrinsicMetadata>,
+}
+
+/// Metadata about the format of extrinsics
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtrinsicMetadata {
+    /// version of the extrinsic format
+    pub version: u8,
+    /// names of the signed extensions, in the order they are encoded
+    pub signed_extensions: Vec<String>,
 }
 
 impl Metadata {
+    /// Create Metadata from modules in the order they are declared in the
+    /// runtime
+    ///
+    /// Modules must already know their index in the outer `Call` and `Event`
+    /// enums, since how those are derived depends on the metadata version
+    pub(crate) fn from_modules(modules: Vec<ModuleMetadata>) -> Self {
+        let mut modules_by_name = HashMap::new();
+        let mut modules_by_event_index = HashMap::new();
+        let mut modules_by_call_index = HashMap::new();
+        for (i, module) in modules.iter().enumerate() {
+            modules_by_name.insert(module.name.clone(), i);
+            if let Some(event_index) = module.event_index {
+                modules_by_event_index.insert(event_index, i);
+            }
+            if let Some(call_index) = module.call_index {
+                modules_by_call_index.insert(call_index, i);
+            }
+        }
+        Metadata {
+            modules: modules.into_iter().map(Rc::new).collect(),
+            modules_by_name,
+            modules_by_event_index,
+            modules_by_call_index,
+            extrinsic: None,
+        }
+    }
+
+    /// the extrinsic format of the runtime,
+    /// if the metadata is recent enough to contain it
+    pub fn extrinsic(&self) -> Option<&ExtrinsicMetadata> {
+        self.extrinsic.as_ref()
+    }
+
     /// Create a new Metadata type from raw encoded bytes
     ///
     /// # Panics
@@ -86,48 +139,55 @@ impl Metadata {
     ///
     /// Panics if decoding into metadata prefixed fails
     pub fn new(bytes: &[u8]) -> Self {
-        // Runtime metadata is a tuple struct with the following fields:
-        // RuntimeMetadataPrefixed(u32, RuntimeMetadata)
-        // this means when it's SCALE encoded, the first four bytes
-        // are the 'u32' prefix, and since `RuntimeMetadata` is an enum,
-        // the first byte is the index of the enum item.
-        // Since RuntimeMetadata is versioned starting from 0, this also corresponds to
-        // the Metadata version
-        let version = bytes[4];
-
-        match version {
-            0x07 => {
-                let meta: runtime_metadata07::RuntimeMetadataPrefixed =
-                    OldDecode::decode(&mut &bytes[..]).expect("Decode failed");
-                meta.try_into().expect("Conversion failed")
-            }
-            0x08 => {
-                let meta: runtime_metadata08::RuntimeMetadataPrefixed =
-                    Decode::decode(&mut &bytes[..]).expect("Decode failed");
-                meta.try_into().expect("Conversion failed")
-            }
-            0x09 => {
-                let meta: runtime_metadata09::RuntimeMetadataPrefixed =
-                    Decode::decode(&mut &bytes[..]).expect("Decode failed");
-                meta.try_into().expect("Conversion failed")
-            }
-            0xA => {
-                let meta: runtime_metadata10::RuntimeMetadataPrefixed =
-                    Decode::decode(&mut &bytes[..]).expect("Decode failed");
-                meta.try_into().expect("Conversion failed")
-            },
-            0xB => {
-                let meta: runtime_metadata_latest::RuntimeMetadataPrefixed =
-                    Decode::decode(&mut &bytes[..]).expect("Decode failed");
-                meta.try_into().expect("Conversion failed")
+        Self::from_bytes(bytes).expect("Decode failed") // TODO remove panic
+    }
+
+    /// Create a new Metadata type from raw encoded bytes,
+    /// returning an error if the metadata cannot be decoded
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Converters::default().convert(bytes)
+    }
+
+    /// Create Metadata from a hex string, as returned by `state_getMetadata`
+    ///
+    /// The `0x` prefix is optional, surrounding whitespace is ignored
+    pub fn from_hex(hex: &str) -> Result<Self, Error> {
+        let hex = hex.trim();
+        let hex = if hex.starts_with("0x") || hex.starts_with("0X") {
+            &hex[2 ..]
+        } else {
+            hex
+        };
+        let bytes = hex::decode(hex).map_err(|e| Error::InvalidHex(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Create Metadata from a full JSON-RPC response to `state_getMetadata`
+    ///
+    /// IE: `{"jsonrpc":"2.0","result":"0x6d657461...","id":1}`
+    pub fn from_rpc_response(response: &str) -> Result<Self, Error> {
+        let response: serde_json::Value = serde_json::from_str(response)
+            .map_err(|e| Error::InvalidRpcResponse(e.to_string()))?;
+        if let Some(error) = response.get("error") {
+            let code = error.get("code").and_then(|c| c.as_i64()).unwrap_or(0);
+            let message = error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("unknown error");
+            Err(Error::RpcError(code, message.to_string()))?;
+        }
+        match response.get("result") {
+            Some(serde_json::Value::String(hex)) => Self::from_hex(hex),
+            Some(_) => {
+                Err(Error::InvalidRpcResponse("`result` is not a string".to_string()))
             }
-            e @ _ => panic!("version {} is unknown, invalid or unsupported", e), /* TODO remove panic */
+            None => Err(Error::InvalidRpcResponse("missing `result`".to_string())),
         }
     }
 
-    /// returns an iterate over all Modules
+    /// returns an iterator over all Modules, in the order they are declared
     pub fn modules(&self) -> impl Iterator<Item = &Rc<ModuleMetadata>> {
-        self.modules.values()
+        self.modules.iter()
     }
 
     /// returns a weak reference to a module from it's name
@@ -136,10 +196,10 @@ impl Metadata {
         S: ToString,
     {
         let name = name.to_string();
-        self.modules
+        self.modules_by_name
             .get(&name)
+            .map(|i| self.modules[*i].clone())
             .ok_or(MetadataError::ModuleNotFound(name))
-            .map(|m| (*m).clone())
     }
 
     pub fn module_exists<S>(&self, name: S) -> bool
@@ -147,40 +207,101 @@ impl Metadata {
         S: ToString,
     {
         let name = name.to_string();
-        self.modules.get(&name).is_some()
+        self.modules_by_name.get(&name).is_some()
+    }
+
+    /// get a module by its index amongst all modules of the runtime
+    pub fn module_by_index(
+        &self, module_index: u8,
+    ) -> Result<Rc<ModuleMetadata>, MetadataError> {
+        self.modules
+            .get(module_index as usize)
+            .cloned()
+            .ok_or(MetadataError::ModuleIndexNotFound(module_index))
+    }
+
+    /// get an error of a module
+    /// by the index of the module amongst all modules of the runtime,
+    /// and the index of the error within the module
+    ///
+    /// These are the indices carried by `DispatchError::Module`
+    pub fn module_error(
+        &self, module_index: u8, error_index: u8,
+    ) -> Result<ModuleErrorMetadata, MetadataError> {
+        self.module_by_index(module_index)?
+            .error(error_index)
+            .cloned()
+            .map_err(|_| MetadataError::ErrorNotFound(module_index, error_index))
     }
 
     /// get the name of a module given it's event index
     pub fn module_name(&self, module_index: u8) -> Result<String, MetadataError> {
+        self.module_by_event_index(module_index)
+            .map(|m| m.name.clone())
+    }
+
+    /// get a module given the index of its variant in the outer `Event` enum
+    pub fn module_by_event_index(
+        &self, module_index: u8,
+    ) -> Result<Rc<ModuleMetadata>, MetadataError> {
         self.modules_by_event_index
             .get(&module_index)
-            .cloned()
+            .map(|i| self.modules[*i].clone())
             .ok_or(MetadataError::EventNotFound(module_index))
     }
 
+    /// get a module given the index of its variant in the outer `Call` enum
+    pub fn module_by_call_index(
+        &self, module_index: u8,
+    ) -> Result<Rc<ModuleMetadata>, MetadataError> {
+        self.modules_by_call_index
+            .get(&module_index)
+            .map(|i| self.modules[*i].clone())
+            .ok_or(MetadataError::ModuleIndexNotFound(module_index))
+    }
+
     /// print out a detailed but human readable description of the module
     /// metadata
     pub fn detailed_pretty(&self) -> String {
         let mut string = String::new();
-        for (name, module) in &self.modules {
-            string.push_str(name.as_str());
+        for module in &self.modules {
+            string.push_str(module.name.as_str());
             string.push('\n');
-            for (storage, meta) in &module.storage {
+            for meta in &module.storage {
                 string.push_str(" S  ");
-                string.push_str(storage.as_str());
+                string.push_str(meta.name.as_str());
                 string.push_str(format!(" TYPE {:?}", meta.ty).as_str());
                 string.push_str(format!(" MOD {:?}", meta.modifier).as_str());
                 string.push('\n');
+                push_docs(&mut string, &meta.documentation);
             }
-            for (call, _) in &module.calls {
+            for meta in &module.calls {
                 string.push_str(" C  ");
-                string.push_str(call.as_str());
+                string.push_str(meta.name.as_str());
+                for arg in &meta.arguments {
+                    string.push_str(format!(" {}: {}", arg.name, arg.ty).as_str());
+                }
                 string.push('\n');
+                push_docs(&mut string, &meta.documentation);
             }
-            for (_, event) in &module.events {
+            for event in &module.events {
                 string.push_str(" E  ");
                 string.push_str(event.name.as_str());
                 string.push('\n');
+                push_docs(&mut string, &event.documentation);
+            }
+            for meta in &module.constants {
+                string.push_str(" K  ");
+                string.push_str(meta.name.as_str());
+                string.push_str(format!(" TYPE {}", meta.ty).as_str());
+                string.push('\n');
+                push_docs(&mut string, &meta.documentation);
+            }
+            for error in &module.errors {
+                string.push_str(" X  ");
+                string.push_str(error.name.as_str());
+                string.push('\n');
+                push_docs(&mut string, &error.documentation);
             }
         }
         string
@@ -189,20 +310,20 @@ impl Metadata {
     /// print out a human readable description of the metadata
     pub fn pretty(&self) -> String {
         let mut string = String::new();
-        for (name, module) in &self.modules {
-            string.push_str(name.as_str());
+        for module in &self.modules {
+            string.push_str(module.name.as_str());
             string.push('\n');
-            for (storage, _) in &module.storage {
+            for storage in &module.storage {
                 string.push_str(" s  ");
-                string.push_str(storage.as_str());
+                string.push_str(storage.name.as_str());
                 string.push('\n');
             }
-            for (call, _) in &module.calls {
+            for call in &module.calls {
                 string.push_str(" c  ");
-                string.push_str(call.as_str());
+                string.push_str(call.name.as_str());
                 string.push('\n');
             }
-            for (_, event) in &module.events {
+            for event in &module.events {
                 string.push_str(" e  ");
                 string.push_str(event.name.as_str());
                 string.push('\n');
@@ -212,18 +333,40 @@ impl Metadata {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// push documentation lines onto a pretty-printed description
+fn push_docs(string: &mut String, docs: &[String]) {
+    for line in docs {
+        string.push_str("      ///");
+        string.push_str(line.as_str());
+        string.push('\n');
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct ModuleMetadata {
-    /// index of the module within StorageMetadata 'Entries'
+    /// index of the module amongst all modules of the runtime
     index: u8,
+    /// index of the module in the outer `Call` enum,
+    /// if the module has calls
+    call_index: Option<u8>,
+    /// index of the module in the outer `Event` enum,
+    /// if the module has events
+    event_index: Option<u8>,
     /// name of the module
     name: String,
-    /// Name of storage entry -> Metadata of storage entry
-    storage: HashMap<String, StorageMetadata>,
-    /// Calls in the module, CallName -> encoded calls
-    calls: HashMap<String, Vec<u8>>,
-    events: HashMap<u8, ModuleEventMetadata>,
-    // constants
+    /// prefix of all storage keys of the module,
+    /// `None` if the module has no storage
+    storage_prefix: Option<String>,
+    /// storage entries, in the order they are declared
+    storage: Vec<StorageMetadata>,
+    /// calls of the module, in the order of their index
+    calls: Vec<CallMetadata>,
+    /// events of the module, in the order of their index
+    events: Vec<ModuleEventMetadata>,
+    /// errors of the module, in the order of their index
+    errors: Vec<ModuleErrorMetadata>,
+    /// constants, in the order they are declared
+    constants: Vec<ModuleConstantMetadata>,
 }
 
 impl ModuleMetadata {
@@ -231,61 +374,204 @@ impl ModuleMetadata {
         &self.name
     }
 
+    /// index of the module amongst all modules of the runtime
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// index of the module in the outer `Call` enum
+    pub fn call_index(&self) -> Option<u8> {
+        self.call_index
+    }
+
+    /// index of the module in the outer `Event` enum
+    pub fn event_index(&self) -> Option<u8> {
+        self.event_index
+    }
+
     /// return the SCALE-encoded Call with parameters appended and parameters
     pub fn call<T: Encode>(
         &self, function: &'static str, params: T,
     ) -> Result<Encoded, MetadataError> {
-        let fn_bytes = self
+        let call = self
             .calls
-            .get(function)
+            .iter()
+            .find(|c| c.name == function)
+            .ok_or(MetadataError::CallNotFound(function))?;
+        let module_index = self
+            .call_index
             .ok_or(MetadataError::CallNotFound(function))?;
-        let mut bytes = vec![self.index];
-        bytes.extend(fn_bytes);
+        let mut bytes = vec![module_index, call.index];
         bytes.extend(params.encode());
         Ok(Encoded(bytes))
     }
 
     /// Return a storage entry by its key
-    pub fn storage(&self, key: &'static str) -> Result<&StorageMetadata, MetadataError> {
+    pub fn storage(&self, key: &str) -> Result<&StorageMetadata, MetadataError> {
         self.storage
-            .get(key)
-            .ok_or(MetadataError::StorageNotFound(key))
+            .iter()
+            .find(|s| s.name == key)
+            .ok_or_else(|| MetadataError::StorageNotFound(key.to_string()))
     }
 
     /// an iterator over all possible events for this module
     pub fn events(&self) -> impl Iterator<Item = &ModuleEventMetadata> {
-        self.events.values()
+        self.events.iter()
     }
 
     // TODO Transfer to Subxt
     /// iterator over all possible calls in this module
-    pub fn calls(&self) -> impl Iterator<Item = (&String, &Vec<u8>)> {
+    pub fn calls(&self) -> impl Iterator<Item = &CallMetadata> {
         self.calls.iter()
     }
 
+    /// get a call by its index in the module
+    pub fn call_by_index(&self, index: u8) -> Result<&CallMetadata, MetadataError> {
+        self.calls
+            .get(index as usize)
+            .ok_or(MetadataError::CallIndexNotFound(index))
+    }
+
     /// iterator over all storage keys in this module
     pub fn storage_keys(&self) -> impl Iterator<Item = (&String, &StorageMetadata)> {
-        self.storage.iter()
+        self.storage.iter().map(|s| (&s.name, s))
     }
 
     /// get an event by its index in the module
     pub fn event(&self, index: u8) -> Result<&ModuleEventMetadata, MetadataError> {
         self.events
-            .get(&index)
+            .get(index as usize)
             .ok_or(MetadataError::EventNotFound(index))
     }
+
+    /// an iterator over all constants of this module
+    pub fn constants(&self) -> impl Iterator<Item = &ModuleConstantMetadata> {
+        self.constants.iter()
+    }
+
+    /// get a constant by its name
+    pub fn constant(
+        &self, name: &'static str,
+    ) -> Result<&ModuleConstantMetadata, MetadataError> {
+        self.constants
+            .iter()
+            .find(|c| c.name == name)
+            .ok_or(MetadataError::ConstantNotFound(name))
+    }
+
+    /// an iterator over all errors of this module
+    pub fn errors(&self) -> impl Iterator<Item = &ModuleErrorMetadata> {
+        self.errors.iter()
+    }
+
+    /// get an error by its index in the module
+    pub fn error(&self, index: u8) -> Result<&ModuleErrorMetadata, MetadataError> {
+        self.errors
+            .get(index as usize)
+            .ok_or(MetadataError::ErrorNotFound(self.index, index))
+    }
+
+    /// the signature of every call, event, storage entry and constant of
+    /// the module, with types named as they appear in the metadata
+    ///
+    /// IE: `call transfer(dest: <T::Lookup as StaticLookup>::Source)`
+    pub fn signatures(&self) -> Vec<String> {
+        let mut signatures = Vec::new();
+        for call in &self.calls {
+            let args = call
+                .arguments
+                .iter()
+                .map(|a| format!("{}: {}", a.name, a.ty))
+                .collect::<Vec<_>>();
+            signatures.push(format!("call {}({})", call.name, args.join(", ")));
+        }
+        for event in &self.events {
+            let args = event
+                .arguments
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>();
+            signatures.push(format!("event {}({})", event.name, args.join(", ")));
+        }
+        for entry in &self.storage {
+            let (ty, _) = diff::storage_signature(&entry.ty);
+            signatures.push(format!("storage {}: {}", entry.name, ty));
+        }
+        for constant in &self.constants {
+            signatures.push(format!("constant {}: {}", constant.name, constant.ty));
+        }
+        signatures
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CallMetadata {
+    /// name of the call
+    name: String,
+    /// index of the call within the module's `Call` enum
+    index: u8,
+    /// arguments of the call, in the order they are encoded
+    arguments: Vec<CallArgMetadata>,
+    documentation: Vec<String>,
+}
+
+impl CallMetadata {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// an iterator over the arguments of this call
+    pub fn arguments(&self) -> impl Iterator<Item = &CallArgMetadata> {
+        self.arguments.iter()
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CallArgMetadata {
+    /// name of the argument
+    pub name: String,
+    /// type of the argument, as declared in the runtime
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct StorageMetadata {
+    /// name of the storage entry
+    name: String,
     prefix: String,
     modifier: StorageEntryModifier,
     ty: StorageEntryType,
+    #[serde(serialize_with = "crate::util::serialize_hex")]
     default: Vec<u8>,
     documentation: Vec<String>,
 }
 
 impl StorageMetadata {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
+    }
+
+    /// type of the value stored, as declared in the runtime
+    pub fn value_type(&self) -> &str {
+        match &self.ty {
+            StorageEntryType::Plain(value) => value,
+            StorageEntryType::Map { value, .. } => value,
+            StorageEntryType::DoubleMap { value, .. } => value,
+        }
+    }
+
     pub fn get_map<K: Encode, V: Decode + Clone>(
         &self,
     ) -> Result<StorageMap<K, V>, MetadataError> {
@@ -307,6 +593,10 @@ impl StorageMetadata {
     }
 }
 
+/// Key of a storage entry, as queried with `state_getStorage`
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct StorageKey(pub Vec<u8>);
+
 #[derive(Clone, Debug)]
 pub struct StorageMap<K, V> {
     _marker: PhantomData<K>,
@@ -320,18 +610,13 @@ impl<K: Encode, V: Decode + Clone> StorageMap<K, V> {
         let mut bytes = self.prefix.clone();
         bytes.extend(key.encode());
         let hash = match self.hasher {
-            StorageHasher::Blake2_128 => {
-                substrate_primitives::blake2_128(&bytes).to_vec()
-            }
-            StorageHasher::Blake2_256 => {
-                substrate_primitives::blake2_256(&bytes).to_vec()
-            }
-            StorageHasher::Blake2_128Concat => {
-                substrate_primitives::blake2_128(&bytes).to_vec()
-            }
-            StorageHasher::Twox128 => substrate_primitives::twox_128(&bytes).to_vec(),
-            StorageHasher::Twox256 => substrate_primitives::twox_256(&bytes).to_vec(),
-            StorageHasher::Twox64Concat => substrate_primitives::twox_64(&bytes).to_vec(),
+            StorageHasher::Blake2_128 => hashing::blake2_128(&bytes).to_vec(),
+            StorageHasher::Blake2_256 => hashing::blake2_256(&bytes).to_vec(),
+            StorageHasher::Blake2_128Concat => hashing::blake2_128(&bytes).to_vec(),
+            StorageHasher::Twox128 => hashing::twox_128(&bytes).to_vec(),
+            StorageHasher::Twox256 => hashing::twox_256(&bytes).to_vec(),
+            StorageHasher::Twox64Concat => hashing::twox_64(&bytes).to_vec(),
+            StorageHasher::Identity => bytes,
         };
         StorageKey(hash)
     }
@@ -341,18 +626,45 @@ impl<K: Encode, V: Decode + Clone> StorageMap<K, V> {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct ModuleEventMetadata {
     pub name: String,
-    pub(crate) arguments: HashSet<EventArg>,
+    /// arguments of the event, in the order they are encoded
+    pub(crate) arguments: Vec<EventArg>,
+    pub(crate) documentation: Vec<String>,
 }
 
 impl ModuleEventMetadata {
     pub fn arguments(&self) -> Vec<EventArg> {
-        self.arguments.iter().cloned().collect()
+        self.arguments.clone()
+    }
+
+    pub fn documentation(&self) -> &[String] {
+        &self.documentation
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ModuleConstantMetadata {
+    /// name of the constant
+    pub name: String,
+    /// type of the constant, as declared in the runtime
+    pub ty: String,
+    /// the SCALE-encoded value of the constant
+    #[serde(serialize_with = "crate::util::serialize_hex")]
+    pub value: Vec<u8>,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ModuleErrorMetadata {
+    /// name of the error
+    pub name: String,
+    /// index of the error within the `Error` enum of the module
+    pub index: u8,
+    pub documentation: Vec<String>,
+}
+
 /// Naive representation of event argument types, supports current set of
 /// substrate EventArg types. If and when Substrate uses `type-metadata`, this
 /// can be replaced.
@@ -383,9 +695,8 @@ impl FromStr for EventArg {
         } else if s.starts_with("(") {
             if s.ends_with(")") {
                 let mut args = Vec::new();
-                for arg in s[1 .. s.len() - 1].split(',') {
-                    let arg = arg.trim().parse()?;
-                    args.push(arg)
+                for arg in split_top_level(&s[1 .. s.len() - 1]) {
+                    args.push(arg.parse()?)
                 }
                 Ok(EventArg::Tuple(args))
             } else {
@@ -400,6 +711,19 @@ impl FromStr for EventArg {
     }
 }
 
+impl fmt::Display for EventArg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EventArg::Primitive(p) => write!(f, "{}", p),
+            EventArg::Vec(arg) => write!(f, "Vec<{}>", arg),
+            EventArg::Tuple(args) => {
+                let args = args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+                write!(f, "({})", args.join(", "))
+            }
+        }
+    }
+}
+
 impl EventArg {
     /// Returns all primitive types for this EventArg
     pub fn primitives(&self) -> Vec<String> {
@@ -417,23 +741,116 @@ impl EventArg {
     }
 }
 
-#[derive(Fail, Debug)]
+#[derive(Debug, derive_more::Display)]
 pub enum Error {
-    #[fail(display = "Invalid Prefix")]
+    #[display(fmt = "Invalid Prefix")]
     InvalidPrefix,
-    #[fail(display = " Invalid Version")]
+    #[display(fmt = " Invalid Version")]
     InvalidVersion,
-    #[fail(display = "Expected Decoded")]
-    ExpectedDecoded,
-    #[fail(display = "Invalid Event {}:{}", _0, _1)]
+    #[display(fmt = "Invalid Event {}:{}", _0, _1)]
     InvalidEventArg(String, &'static str),
+    #[display(fmt = "Metadata too short: {} bytes", _0)]
+    TooShort(usize),
+    #[display(fmt = "Unsupported metadata version {}", _0)]
+    UnsupportedVersion(u8),
+    #[display(fmt = "Failed to decode V{} metadata: {}", _0, _1)]
+    Decode(u8, String),
+    #[display(fmt = "More than 256 {} in metadata", _0)]
+    TooMany(&'static str),
+    #[display(fmt = "Invalid hex: {}", _0)]
+    InvalidHex(String),
+    #[display(fmt = "Invalid JSON-RPC response: {}", _0)]
+    InvalidRpcResponse(String),
+    #[display(fmt = "JSON-RPC error {}: {}", _0, _1)]
+    RpcError(i64, String),
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
     use crate::test_suite;
 
+    #[test]
+    fn should_keep_documentation() {
+        let meta = super::test_suite::test_metadata();
+        let module = meta.module("TestModule0").unwrap();
+        let call = module.call_by_index(0).unwrap();
+        assert_eq!(call.documentation(), &["Some Kind of call docs".to_string()]);
+        let event = module.event(0).unwrap();
+        assert_eq!(event.documentation(), &["Some Kind of event docs".to_string()]);
+        let constant = module.constant("TestConstant0").unwrap();
+        assert_eq!(constant.documentation, vec!["Some Kind of constant docs"]);
+        assert!(meta.detailed_pretty().contains("///Some Kind of constant docs"));
+    }
+
+    #[test]
+    fn should_keep_declaration_order() {
+        let meta = super::test_suite::test_metadata();
+        let names = meta.modules().map(|m| m.name().to_string()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["TestModule0", "TestModule1", "TestModule2", "System"]);
+        let module = meta.module("TestModule1").unwrap();
+        let calls = module.calls().map(|c| c.index()).collect::<Vec<_>>();
+        assert_eq!(calls, vec![0, 1, 2, 3]);
+        assert!(meta.pretty().starts_with("TestModule0\n s  TestStorage0\n s  TestStorage1"));
+    }
+
+    #[test]
+    fn should_get_modules_by_index() {
+        let meta = super::test_suite::test_metadata();
+        // System has no calls, so it is not part of the outer `Call` enum
+        assert!(meta.module_by_call_index(3).is_err());
+        assert_eq!(meta.module_by_event_index(3).unwrap().name(), "System");
+        assert_eq!(meta.module_by_index(3).unwrap().name(), "System");
+        assert_eq!(meta.module_by_call_index(1).unwrap().call_index(), Some(1));
+    }
+
+    #[test]
+    fn should_get_module_error() {
+        let meta = super::test_suite::test_metadata();
+        let err = meta.module_error(1, 1).unwrap();
+        assert_eq!(err.name, "TestError1");
+        assert_eq!(err.index, 1);
+        assert!(meta.module_error(1, 9).is_err());
+        assert!(meta.module_error(9, 0).is_err());
+    }
+
+    #[test]
+    fn should_create_metadata_from_hex() {
+        let bytes = test_suite::runtime_v9();
+        let meta = Metadata::from_bytes(&bytes).unwrap();
+        let hex = format!("0x{}", hex::encode(&bytes));
+        assert_eq!(Metadata::from_hex(&hex).unwrap(), meta);
+        assert_eq!(Metadata::from_hex(&hex[2 ..]).unwrap(), meta);
+
+        let response = format!(r#"{{"jsonrpc":"2.0","result":"{}","id":1}}"#, hex);
+        assert_eq!(Metadata::from_rpc_response(&response).unwrap(), meta);
+    }
+
+    #[test]
+    fn should_not_create_metadata_from_malformed_input() {
+        let err = |r: Result<Metadata, Error>| r.unwrap_err().to_string();
+        assert_eq!(err(Metadata::from_hex("0x6d65")), "Metadata too short: 2 bytes");
+        assert!(err(Metadata::from_hex("0xzz")).starts_with("Invalid hex"));
+        assert_eq!(
+            err(Metadata::from_hex("0x6d65746101")),
+            "Unsupported metadata version 1"
+        );
+        assert!(err(Metadata::from_hex("0x6d657461090102")).starts_with("Failed to decode V9"));
+        assert!(err(Metadata::from_rpc_response("{")).starts_with("Invalid JSON-RPC"));
+        assert_eq!(
+            err(Metadata::from_rpc_response(r#"{"jsonrpc":"2.0","id":1}"#)),
+            "Invalid JSON-RPC response: missing `result`"
+        );
+        let response = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"Method not found"},"id":1}"#;
+        assert_eq!(
+            err(Metadata::from_rpc_response(response)),
+            "JSON-RPC error -32601: Method not found"
+        );
+    }
+
     #[test]
     fn should_create_metadata() {
         let meta = test_suite::runtime_v9();
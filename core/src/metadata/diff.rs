@@ -0,0 +1,670 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Differences between the metadata of two runtime versions
+//!
+//! Modules are matched by name. A module which was removed while a module
+//! with calls, events, storage and constants of the same names and types was
+//! added is reported as renamed, even if their documentation or the values
+//! of their constants changed. Calls, events, storage entries and constants are
+//! matched by name within a module
+
+use super::{Metadata, ModuleMetadata, StorageMetadata};
+use super::frame::latest::StorageEntryType;
+use crate::{prelude::*, util::to_hex};
+use serde::Serialize;
+use core::fmt;
+
+/// Every change between two versions of the metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetadataDiff {
+    pub changes: Vec<Change>,
+}
+
+/// Position of a module in the runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ModuleIndices {
+    /// index amongst all modules
+    pub index: u8,
+    /// index in the outer `Call` enum
+    pub call_index: Option<u8>,
+    /// index in the outer `Event` enum
+    pub event_index: Option<u8>,
+}
+
+/// A single change to the metadata
+///
+/// Types are rendered the way they appear in the metadata
+/// (IE: `T::AccountId`), call arguments as `name: Type`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change")]
+pub enum Change {
+    ModuleAdded {
+        module: String,
+    },
+    ModuleRemoved {
+        module: String,
+    },
+    ModuleRenamed {
+        from: String,
+        to: String,
+    },
+    ModuleIndicesChanged {
+        module: String,
+        old: ModuleIndices,
+        new: ModuleIndices,
+    },
+    CallAdded {
+        module: String,
+        call: String,
+    },
+    CallRemoved {
+        module: String,
+        call: String,
+    },
+    CallSignatureChanged {
+        module: String,
+        call: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    CallIndexChanged {
+        module: String,
+        call: String,
+        old: u8,
+        new: u8,
+    },
+    EventAdded {
+        module: String,
+        event: String,
+    },
+    EventRemoved {
+        module: String,
+        event: String,
+    },
+    EventArgumentsChanged {
+        module: String,
+        event: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    EventIndexChanged {
+        module: String,
+        event: String,
+        old: u8,
+        new: u8,
+    },
+    StorageAdded {
+        module: String,
+        storage: String,
+    },
+    StorageRemoved {
+        module: String,
+        storage: String,
+    },
+    StorageTypeChanged {
+        module: String,
+        storage: String,
+        old: String,
+        new: String,
+    },
+    StorageHasherChanged {
+        module: String,
+        storage: String,
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    StorageModifierChanged {
+        module: String,
+        storage: String,
+        old: String,
+        new: String,
+    },
+    ConstantAdded {
+        module: String,
+        constant: String,
+    },
+    ConstantRemoved {
+        module: String,
+        constant: String,
+    },
+    ConstantTypeChanged {
+        module: String,
+        constant: String,
+        old: String,
+        new: String,
+    },
+    ConstantValueChanged {
+        module: String,
+        constant: String,
+        /// `0x`-prefixed SCALE-encoded value
+        old: String,
+        /// `0x`-prefixed SCALE-encoded value
+        new: String,
+    },
+}
+
+impl Change {
+    /// name of the module this change applies to
+    ///
+    /// for renamed modules, this is the new name
+    pub fn module(&self) -> &str {
+        match self {
+            Change::ModuleRenamed { to, .. } => to.as_str(),
+            Change::ModuleAdded { module }
+            | Change::ModuleRemoved { module }
+            | Change::ModuleIndicesChanged { module, .. }
+            | Change::CallAdded { module, .. }
+            | Change::CallRemoved { module, .. }
+            | Change::CallSignatureChanged { module, .. }
+            | Change::CallIndexChanged { module, .. }
+            | Change::EventAdded { module, .. }
+            | Change::EventRemoved { module, .. }
+            | Change::EventArgumentsChanged { module, .. }
+            | Change::EventIndexChanged { module, .. }
+            | Change::StorageAdded { module, .. }
+            | Change::StorageRemoved { module, .. }
+            | Change::StorageTypeChanged { module, .. }
+            | Change::StorageHasherChanged { module, .. }
+            | Change::StorageModifierChanged { module, .. }
+            | Change::ConstantAdded { module, .. }
+            | Change::ConstantRemoved { module, .. }
+            | Change::ConstantTypeChanged { module, .. }
+            | Change::ConstantValueChanged { module, .. } => module.as_str(),
+        }
+    }
+
+    /// returns true if this change may alter how a type of the module
+    /// is encoded, meaning type registrations for the module must be
+    /// reviewed
+    pub fn affects_types(&self) -> bool {
+        match self {
+            Change::ModuleAdded { .. }
+            | Change::ModuleRenamed { .. }
+            | Change::CallAdded { .. }
+            | Change::CallSignatureChanged { .. }
+            | Change::EventAdded { .. }
+            | Change::EventArgumentsChanged { .. }
+            | Change::StorageAdded { .. }
+            | Change::StorageTypeChanged { .. }
+            | Change::ConstantAdded { .. }
+            | Change::ConstantTypeChanged { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::ModuleAdded { module } => write!(f, "+ {}", module),
+            Change::ModuleRemoved { module } => write!(f, "- {}", module),
+            Change::ModuleRenamed { from, to } => {
+                write!(f, "~ {} renamed to {}", from, to)
+            }
+            Change::ModuleIndicesChanged { module, old, new } => write!(
+                f,
+                "~ {} index {} -> {}, call index {} -> {}, event index {} -> {}",
+                module,
+                old.index,
+                new.index,
+                display_index(old.call_index),
+                display_index(new.call_index),
+                display_index(old.event_index),
+                display_index(new.event_index)
+            ),
+            Change::CallAdded { module, call } => {
+                write!(f, "+ {}::{} (call)", module, call)
+            }
+            Change::CallRemoved { module, call } => {
+                write!(f, "- {}::{} (call)", module, call)
+            }
+            Change::CallSignatureChanged { module, call, old, new } => write!(
+                f,
+                "~ {}::{} ({}) -> ({})",
+                module,
+                call,
+                old.join(", "),
+                new.join(", ")
+            ),
+            Change::CallIndexChanged { module, call, old, new } => {
+                write!(f, "~ {}::{} call index {} -> {}", module, call, old, new)
+            }
+            Change::EventAdded { module, event } => {
+                write!(f, "+ {}::{} (event)", module, event)
+            }
+            Change::EventRemoved { module, event } => {
+                write!(f, "- {}::{} (event)", module, event)
+            }
+            Change::EventArgumentsChanged { module, event, old, new } => write!(
+                f,
+                "~ {}::{} ({}) -> ({})",
+                module,
+                event,
+                old.join(", "),
+                new.join(", ")
+            ),
+            Change::EventIndexChanged { module, event, old, new } => {
+                write!(f, "~ {}::{} event index {} -> {}", module, event, old, new)
+            }
+            Change::StorageAdded { module, storage } => {
+                write!(f, "+ {}::{} (storage)", module, storage)
+            }
+            Change::StorageRemoved { module, storage } => {
+                write!(f, "- {}::{} (storage)", module, storage)
+            }
+            Change::StorageTypeChanged { module, storage, old, new } => {
+                write!(f, "~ {}::{} {} -> {}", module, storage, old, new)
+            }
+            Change::StorageHasherChanged { module, storage, old, new } => write!(
+                f,
+                "~ {}::{} hashers {} -> {}",
+                module,
+                storage,
+                old.join(", "),
+                new.join(", ")
+            ),
+            Change::StorageModifierChanged { module, storage, old, new } => {
+                write!(f, "~ {}::{} modifier {} -> {}", module, storage, old, new)
+            }
+            Change::ConstantAdded { module, constant } => {
+                write!(f, "+ {}::{} (constant)", module, constant)
+            }
+            Change::ConstantRemoved { module, constant } => {
+                write!(f, "- {}::{} (constant)", module, constant)
+            }
+            Change::ConstantTypeChanged { module, constant, old, new } => {
+                write!(f, "~ {}::{} {} -> {}", module, constant, old, new)
+            }
+            Change::ConstantValueChanged { module, constant, old, new } => {
+                write!(f, "~ {}::{} = {} -> {}", module, constant, old, new)
+            }
+        }
+    }
+}
+
+impl MetadataDiff {
+    /// compute the changes from `old` to `new`
+    pub fn new(old: &Metadata, new: &Metadata) -> Self {
+        let mut changes = Vec::new();
+
+        let removed = old
+            .modules()
+            .filter(|m| !new.module_exists(m.name()))
+            .collect::<Vec<_>>();
+        let mut added = new
+            .modules()
+            .filter(|m| !old.module_exists(m.name()))
+            .collect::<Vec<_>>();
+
+        for module in removed {
+            let renamed = added
+                .iter()
+                .position(|m| !is_empty(m) && same_items(module, m));
+            match renamed {
+                Some(i) => {
+                    let to = added.remove(i);
+                    changes.push(Change::ModuleRenamed {
+                        from: module.name().to_string(),
+                        to: to.name().to_string(),
+                    });
+                    diff_indices(module, &to, &mut changes);
+                }
+                None => changes.push(Change::ModuleRemoved {
+                    module: module.name().to_string(),
+                }),
+            }
+        }
+        for module in added {
+            changes.push(Change::ModuleAdded {
+                module: module.name().to_string(),
+            });
+        }
+
+        for new_module in new.modules() {
+            if let Ok(old_module) = old.module(new_module.name()) {
+                diff_indices(&old_module, new_module, &mut changes);
+                diff_module(&old_module, new_module, &mut changes);
+            }
+        }
+
+        MetadataDiff { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// all changes which apply to `module`
+    pub fn module_changes<'a>(
+        &'a self, module: &'a str,
+    ) -> impl Iterator<Item = &'a Change> + 'a {
+        self.changes.iter().filter(move |c| c.module() == module)
+    }
+
+    /// names of the modules whose type registrations should be reviewed
+    pub fn modules_affecting_types(&self) -> Vec<&str> {
+        let mut modules = Vec::new();
+        for change in self.changes.iter().filter(|c| c.affects_types()) {
+            if !modules.contains(&change.module()) {
+                modules.push(change.module());
+            }
+        }
+        modules
+    }
+
+    /// human readable description of the changes, one per line
+    pub fn pretty(&self) -> String {
+        let mut string = String::new();
+        for change in &self.changes {
+            string.push_str(&change.to_string());
+            string.push('\n');
+        }
+        string
+    }
+}
+
+impl Metadata {
+    /// compute the changes from this metadata to `new`
+    pub fn diff(&self, new: &Metadata) -> MetadataDiff {
+        MetadataDiff::new(self, new)
+    }
+}
+
+fn display_index(index: Option<u8>) -> String {
+    index.map(|i| i.to_string()).unwrap_or_else(|| "none".to_string())
+}
+
+fn is_empty(module: &ModuleMetadata) -> bool {
+    module.calls.is_empty()
+        && module.events.is_empty()
+        && module.storage.is_empty()
+        && module.constants.is_empty()
+}
+
+/// modules have calls, events, storage and constants of the same names and
+/// types
+fn same_items(a: &ModuleMetadata, b: &ModuleMetadata) -> bool {
+    a.signatures() == b.signatures()
+}
+
+fn diff_indices(old: &ModuleMetadata, new: &ModuleMetadata, changes: &mut Vec<Change>) {
+    let old_indices = indices(old);
+    let new_indices = indices(new);
+    if old_indices != new_indices {
+        changes.push(Change::ModuleIndicesChanged {
+            module: new.name().to_string(),
+            old: old_indices,
+            new: new_indices,
+        });
+    }
+}
+
+fn indices(module: &ModuleMetadata) -> ModuleIndices {
+    ModuleIndices {
+        index: module.index,
+        call_index: module.call_index,
+        event_index: module.event_index,
+    }
+}
+
+fn diff_module(old: &ModuleMetadata, new: &ModuleMetadata, changes: &mut Vec<Change>) {
+    let module = new.name().to_string();
+
+    for call in old.calls.iter().filter(|c| !new.calls.iter().any(|n| n.name == c.name)) {
+        changes.push(Change::CallRemoved {
+            module: module.clone(),
+            call: call.name.clone(),
+        });
+    }
+    for call in &new.calls {
+        let old_call = match old.calls.iter().find(|c| c.name == call.name) {
+            Some(c) => c,
+            None => {
+                changes.push(Change::CallAdded {
+                    module: module.clone(),
+                    call: call.name.clone(),
+                });
+                continue
+            }
+        };
+        if old_call.arguments != call.arguments {
+            let signature = |c: &super::CallMetadata| {
+                c.arguments
+                    .iter()
+                    .map(|a| format!("{}: {}", a.name, a.ty))
+                    .collect::<Vec<_>>()
+            };
+            changes.push(Change::CallSignatureChanged {
+                module: module.clone(),
+                call: call.name.clone(),
+                old: signature(old_call),
+                new: signature(call),
+            });
+        }
+        if old_call.index != call.index {
+            changes.push(Change::CallIndexChanged {
+                module: module.clone(),
+                call: call.name.clone(),
+                old: old_call.index,
+                new: call.index,
+            });
+        }
+    }
+
+    for event in
+        old.events.iter().filter(|e| !new.events.iter().any(|n| n.name == e.name))
+    {
+        changes.push(Change::EventRemoved {
+            module: module.clone(),
+            event: event.name.clone(),
+        });
+    }
+    for (index, event) in new.events.iter().enumerate() {
+        let (old_index, old_event) =
+            match old.events.iter().enumerate().find(|(_, e)| e.name == event.name) {
+                Some(e) => e,
+                None => {
+                    changes.push(Change::EventAdded {
+                        module: module.clone(),
+                        event: event.name.clone(),
+                    });
+                    continue
+                }
+            };
+        if old_event.arguments != event.arguments {
+            let arguments = |args: &[super::EventArg]| {
+                args.iter().map(|a| a.to_string()).collect::<Vec<_>>()
+            };
+            changes.push(Change::EventArgumentsChanged {
+                module: module.clone(),
+                event: event.name.clone(),
+                old: arguments(&old_event.arguments),
+                new: arguments(&event.arguments),
+            });
+        }
+        if old_index != index {
+            changes.push(Change::EventIndexChanged {
+                module: module.clone(),
+                event: event.name.clone(),
+                old: old_index as u8,
+                new: index as u8,
+            });
+        }
+    }
+
+    for entry in
+        old.storage.iter().filter(|s| !new.storage.iter().any(|n| n.name == s.name))
+    {
+        changes.push(Change::StorageRemoved {
+            module: module.clone(),
+            storage: entry.name.clone(),
+        });
+    }
+    for entry in &new.storage {
+        let old_entry = match old.storage.iter().find(|s| s.name == entry.name) {
+            Some(s) => s,
+            None => {
+                changes.push(Change::StorageAdded {
+                    module: module.clone(),
+                    storage: entry.name.clone(),
+                });
+                continue
+            }
+        };
+        diff_storage(&module, old_entry, entry, changes);
+    }
+
+    for constant in old
+        .constants
+        .iter()
+        .filter(|c| !new.constants.iter().any(|n| n.name == c.name))
+    {
+        changes.push(Change::ConstantRemoved {
+            module: module.clone(),
+            constant: constant.name.clone(),
+        });
+    }
+    for constant in &new.constants {
+        let old_constant = match old.constants.iter().find(|c| c.name == constant.name) {
+            Some(c) => c,
+            None => {
+                changes.push(Change::ConstantAdded {
+                    module: module.clone(),
+                    constant: constant.name.clone(),
+                });
+                continue
+            }
+        };
+        if old_constant.ty != constant.ty {
+            changes.push(Change::ConstantTypeChanged {
+                module: module.clone(),
+                constant: constant.name.clone(),
+                old: old_constant.ty.clone(),
+                new: constant.ty.clone(),
+            });
+        }
+        if old_constant.value != constant.value {
+            changes.push(Change::ConstantValueChanged {
+                module: module.clone(),
+                constant: constant.name.clone(),
+                old: to_hex(&old_constant.value),
+                new: to_hex(&constant.value),
+            });
+        }
+    }
+}
+
+fn diff_storage(
+    module: &str, old: &StorageMetadata, new: &StorageMetadata, changes: &mut Vec<Change>,
+) {
+    let (old_ty, old_hashers) = storage_signature(&old.ty);
+    let (new_ty, new_hashers) = storage_signature(&new.ty);
+    if old_ty != new_ty {
+        changes.push(Change::StorageTypeChanged {
+            module: module.to_string(),
+            storage: new.name.clone(),
+            old: old_ty,
+            new: new_ty,
+        });
+    }
+    if old_hashers != new_hashers {
+        changes.push(Change::StorageHasherChanged {
+            module: module.to_string(),
+            storage: new.name.clone(),
+            old: old_hashers,
+            new: new_hashers,
+        });
+    }
+    if old.modifier != new.modifier {
+        changes.push(Change::StorageModifierChanged {
+            module: module.to_string(),
+            storage: new.name.clone(),
+            old: format!("{:?}", old.modifier),
+            new: format!("{:?}", new.modifier),
+        });
+    }
+}
+
+/// the type of a storage entry and the hashers of its keys
+pub(crate) fn storage_signature(ty: &StorageEntryType) -> (String, Vec<String>) {
+    match ty {
+        StorageEntryType::Plain(value) => (value.clone(), Vec::new()),
+        StorageEntryType::Map {
+            hasher, key, value, ..
+        } => (
+            format!("Map<{}, {}>", key, value),
+            vec![format!("{:?}", hasher)],
+        ),
+        StorageEntryType::DoubleMap {
+            hasher,
+            key1,
+            key2,
+            value,
+            key2_hasher,
+        } => (
+            format!("DoubleMap<{}, {}, {}>", key1, key2, value),
+            vec![format!("{:?}", hasher), format!("{:?}", key2_hasher)],
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_suite;
+
+    #[test]
+    fn should_diff_metadata() {
+        let old = test_suite::test_metadata();
+        assert!(old.diff(&old).is_empty());
+
+        let mut modules = old.modules().map(|m| (**m).clone()).collect::<Vec<_>>();
+        modules[0].name = "Renamed".to_string();
+        modules[0].calls[0].documentation = vec!["Edited docs".to_string()];
+        modules[1].calls[1].arguments[0].ty = "Compact<u128>".to_string();
+        modules[1].constants[0].value = vec![0, 0, 0, 0];
+        modules[2].events.remove(0);
+        let new = Metadata::from_modules(modules);
+
+        let diff = old.diff(&new);
+        assert!(diff.changes.contains(&Change::ModuleRenamed {
+            from: "TestModule0".to_string(),
+            to: "Renamed".to_string(),
+        }));
+        assert!(diff.changes.contains(&Change::CallSignatureChanged {
+            module: "TestModule1".to_string(),
+            call: "TestCall1".to_string(),
+            old: vec!["amount: Compact<u64>".to_string(), "remark: Vec<u8>".to_string()],
+            new: vec!["amount: Compact<u128>".to_string(), "remark: Vec<u8>".to_string()],
+        }));
+        assert!(diff.changes.contains(&Change::ConstantValueChanged {
+            module: "TestModule1".to_string(),
+            constant: "TestConstant0".to_string(),
+            old: "0x70170000".to_string(),
+            new: "0x00000000".to_string(),
+        }));
+        assert!(diff.changes.contains(&Change::EventRemoved {
+            module: "TestModule2".to_string(),
+            event: "TestEvent0".to_string(),
+        }));
+        assert_eq!(diff.modules_affecting_types(), vec!["Renamed", "TestModule1"]);
+        assert!(diff.pretty().contains("~ TestModule0 renamed to Renamed\n"));
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["changes"][0]["change"], "ModuleRenamed");
+    }
+}
@@ -0,0 +1,33 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Minimal SCALE definitions of every supported metadata version
+//!
+//! These replace the `frame-metadata` crates of each substrate revision.
+//! Only the encoding matters for decoding: the runtime encodes its
+//! `DecodeDifferent` fields as the value they hold, so owned strings and
+//! vectors decode the exact same bytes
+
+pub mod v07;
+pub mod v08;
+pub mod v09;
+pub mod v10;
+pub mod v11;
+
+pub use self::v11 as latest;
+
+/// 'meta' in little endian, the prefix of the encoded metadata of every version
+pub const META_RESERVED: u32 = 0x6174_656d;
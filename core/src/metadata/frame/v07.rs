@@ -0,0 +1,121 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! SCALE definitions of V7 metadata, the last version before Kusama.
+//! Errors are not part of the metadata yet
+
+use codec::{Decode, Encode};
+use crate::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct RuntimeMetadataPrefixed(pub u32, pub RuntimeMetadata);
+
+/// Only V7 is part of the enum, any other version fails to decode
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub enum RuntimeMetadata {
+    #[codec(index = "7")]
+    V7(RuntimeMetadataV7),
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct RuntimeMetadataV7 {
+    pub modules: Vec<ModuleMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct ModuleMetadata {
+    pub name: String,
+    pub storage: Option<StorageMetadata>,
+    pub calls: Option<Vec<FunctionMetadata>>,
+    pub event: Option<Vec<EventMetadata>>,
+    pub constants: Vec<ModuleConstantMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct StorageMetadata {
+    pub prefix: String,
+    pub entries: Vec<StorageEntryMetadata>,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct StorageEntryMetadata {
+    pub name: String,
+    pub modifier: StorageEntryModifier,
+    pub ty: StorageEntryType,
+    pub default: Vec<u8>,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum StorageEntryModifier {
+    Optional,
+    Default,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum StorageHasher {
+    Blake2_128,
+    Blake2_256,
+    Twox128,
+    Twox256,
+    Twox64Concat,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+pub enum StorageEntryType {
+    Plain(String),
+    Map {
+        hasher: StorageHasher,
+        key: String,
+        value: String,
+        is_linked: bool,
+    },
+    DoubleMap {
+        hasher: StorageHasher,
+        key1: String,
+        key2: String,
+        value: String,
+        key2_hasher: StorageHasher,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct FunctionMetadata {
+    pub name: String,
+    pub arguments: Vec<FunctionArgumentMetadata>,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct FunctionArgumentMetadata {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct EventMetadata {
+    pub name: String,
+    pub arguments: Vec<String>,
+    pub documentation: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Encode, Decode)]
+pub struct ModuleConstantMetadata {
+    pub name: String,
+    pub ty: String,
+    pub value: Vec<u8>,
+    pub documentation: Vec<String>,
+}
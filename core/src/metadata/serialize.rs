@@ -0,0 +1,133 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JSON representation of the unified metadata model
+//!
+//! The schema only depends on the unified model, so it is the same for
+//! metadata of every version from V7 to V11:
+//!
+//! ```json
+//! {
+//!   "modules": [{
+//!     "index": 5,                 // index amongst all modules
+//!     "call_index": 4,            // index in the outer `Call` enum, or null
+//!     "event_index": 4,           // index in the outer `Event` enum, or null
+//!     "name": "Balances",
+//!     "storage_prefix": "Balances", // or null if the module has no storage
+//!     "storage": [{
+//!       "name": "FreeBalance",
+//!       "prefix": "Balances FreeBalance",
+//!       "modifier": "Default",    // "Optional" | "Default"
+//!       "ty": { "Map": {
+//!         "hasher": "Blake2_256",
+//!         "key": "T::AccountId",
+//!         "value": "T::Balance",
+//!         "is_linked": false
+//!       }},                       // or { "Plain": "T::Balance" } or
+//!                                 // { "DoubleMap": { "hasher", "key1", "key2",
+//!                                 //   "value", "key2_hasher" } }
+//!       "default": "0x00000000000000000000000000000000",
+//!       "documentation": [" The 'free' balance of a given account."]
+//!     }],
+//!     "calls": [{
+//!       "name": "transfer",
+//!       "index": 0,
+//!       "arguments": [{ "name": "dest", "ty": "<T::Lookup as StaticLookup>::Source" }],
+//!       "documentation": []
+//!     }],
+//!     "events": [{
+//!       "name": "Transfer",
+//!       "arguments": ["AccountId", "AccountId", "Balance", "Balance"],
+//!       "documentation": []
+//!     }],
+//!     "errors": [{ "name": "VestingBalance", "index": 0, "documentation": [] }],
+//!     "constants": [{
+//!       "name": "ExistentialDeposit",
+//!       "ty": "T::Balance",
+//!       "value": "0x00e40b54020000000000000000000000",
+//!       "documentation": []
+//!     }]
+//!   }]
+//! }
+//! ```
+//!
+//! Byte values (storage defaults and constants) are `0x`-prefixed hex,
+//! event arguments are rendered the way they appear in the metadata.
+//! Calls, events and errors are listed in the order of their index,
+//! everything else in the order it is declared in the runtime
+
+use super::{EventArg, Metadata};
+use crate::prelude::*;
+use serde::{
+    ser::{SerializeSeq, SerializeStruct},
+    Serialize, Serializer,
+};
+
+impl Serialize for Metadata {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Metadata", 1)?;
+        state.serialize_field("modules", &Modules(self))?;
+        state.end()
+    }
+}
+
+struct Modules<'a>(&'a Metadata);
+
+impl<'a> Serialize for Modules<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.0.modules.len()))?;
+        for module in self.0.modules() {
+            seq.serialize_element(&**module)?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for EventArg {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::test_suite;
+
+    #[test]
+    fn should_serialize_metadata() {
+        let meta = test_suite::test_metadata();
+        let json = serde_json::to_value(&meta).unwrap();
+        let module = &json["modules"][0];
+        assert_eq!(module["name"], "TestModule0");
+        assert_eq!(module["call_index"], 0);
+        assert_eq!(module["storage"][0]["ty"]["Plain"], "T::Moment");
+        assert_eq!(module["storage"][2]["modifier"], "Optional");
+        assert_eq!(module["calls"][1]["arguments"][0]["ty"], "Compact<u64>");
+        assert_eq!(module["events"][0]["arguments"][0], "TestEvent0");
+        assert_eq!(module["constants"][0]["value"], "0x70170000");
+        assert_eq!(json["modules"][3]["call_index"], serde_json::Value::Null);
+    }
+}
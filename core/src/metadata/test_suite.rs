@@ -18,139 +18,188 @@
 
 use super::*;
 
-type DecodeDifferentStr = DecodeDifferent<&'static str, String>;
-
 pub fn test_metadata() -> Metadata {
-    Metadata {
-        modules: module_metadata_mock(),
-        modules_by_event_index: HashMap::new(), // not testing this
-    }
+    Metadata::from_modules(module_metadata_mock())
 }
 
-fn module_metadata_mock() -> HashMap<String, Rc<ModuleMetadata>> {
-    let mut map = HashMap::new();
+/// change the type of the first argument of a call
+pub fn with_call_argument(
+    metadata: &Metadata, module: &str, call: &str, ty: &str,
+) -> Metadata {
+    let modules = metadata
+        .modules()
+        .map(|m| {
+            let mut m = (**m).clone();
+            if m.name == module {
+                for c in m.calls.iter_mut().filter(|c| c.name == call) {
+                    c.arguments[0].ty = ty.to_string();
+                }
+            }
+            m
+        })
+        .collect();
+    Metadata::from_modules(modules)
+}
 
-    map.insert(
-        "TestModule0".to_string(),
-        Rc::new(ModuleMetadata {
-            index: 0,
-            name: "TestModule0".to_string(),
-            storage: storage_mock(),
-            calls: call_mock(),
-            events: event_mock(),
-        }),
-    );
-
-    map.insert(
-        "TestModule1".to_string(),
-        Rc::new(ModuleMetadata {
-            index: 1,
-            name: "TestModule1".to_string(),
-            storage: storage_mock(),
-            calls: call_mock(),
-            events: event_mock(),
-        }),
-    );
-
-    map.insert(
-        "TestModule2".to_string(),
-        Rc::new(ModuleMetadata {
-            index: 2,
-            name: "TestModule2".to_string(),
+/// remove a module
+pub fn without_module(metadata: &Metadata, module: &str) -> Metadata {
+    let modules = metadata
+        .modules()
+        .filter(|m| m.name != module)
+        .map(|m| (**m).clone())
+        .collect();
+    Metadata::from_modules(modules)
+}
+
+fn module_metadata_mock() -> Vec<ModuleMetadata> {
+    let mut modules = (0 .. 3)
+        .map(|index| ModuleMetadata {
+            index,
+            call_index: Some(index),
+            event_index: Some(index),
+            name: format!("TestModule{}", index),
+            storage_prefix: Some(format!("TestModule{}", index)),
             storage: storage_mock(),
             calls: call_mock(),
             events: event_mock(),
-        }),
-    );
-
-    map
+            errors: error_mock(),
+            constants: constant_mock(),
+        })
+        .collect::<Vec<_>>();
+
+    modules.push(ModuleMetadata {
+        index: 3,
+        call_index: None,
+        event_index: Some(3),
+        name: "System".to_string(),
+        storage_prefix: None,
+        storage: Vec::new(),
+        calls: Vec::new(),
+        events: system_event_mock(),
+        errors: Vec::new(),
+        constants: Vec::new(),
+    });
+
+    modules
 }
 
-fn storage_mock() -> HashMap<String, StorageMetadata> {
-    let mut map = HashMap::new();
-    let moment = DecodeDifferentStr::Decoded("T::Moment".to_string());
-    let usize_t = DecodeDifferentStr::Decoded("usize".to_string());
+fn storage_mock() -> Vec<StorageMetadata> {
+    let mut storage = Vec::new();
+    let moment = "T::Moment".to_string();
+    let usize_t = "usize".to_string();
     // TODO supposed to be float type but type-metadata does not support
     // floats yet
-    let precision = DecodeDifferentStr::Decoded("F::Precision".to_string());
-
-    map.insert(
-        "TestStorage0".to_string(),
-        StorageMetadata {
-            prefix: "TestStorage0".to_string(),
-            modifier: StorageEntryModifier::Default,
-            ty: StorageEntryType::Plain(moment.clone()),
-            default: vec![112, 23, 0, 0, 0, 0, 0, 0],
-            documentation: vec!["Some Kind of docs".to_string()],
-        },
-    );
-
-    map.insert(
-        "TestStorage1".to_string(),
-        StorageMetadata {
-            prefix: "TestStorage1".to_string(),
-            modifier: StorageEntryModifier::Default,
-            ty: StorageEntryType::Plain(usize_t),
-            default: vec![0, 0, 0, 0, 0, 0, 0, 0],
-            documentation: vec!["Some Kind of docs 2".to_string()],
-        },
-    );
-
-    map.insert(
-        "TestStorage2".to_string(),
-        StorageMetadata {
-            prefix: "TestStorage2".to_string(),
-            modifier: StorageEntryModifier::Optional,
-            ty: StorageEntryType::Plain(moment),
-            default: vec![0, 0, 0, 0, 0, 0, 0, 0],
-            documentation: vec!["Some Kind of docs 2".to_string()],
-        },
-    );
-
-    map.insert(
-        "TestStorage3".to_string(),
-        StorageMetadata {
-            prefix: "TestStorage3".to_string(),
-            modifier: StorageEntryModifier::Optional,
-            ty: StorageEntryType::Plain(precision),
-            default: vec![0, 0, 0, 0, 0, 0, 0, 0],
-            documentation: vec!["Some Kind of docs 3".to_string()],
-        },
-    );
-    map
+    let precision = "F::Precision".to_string();
+
+    storage.push(StorageMetadata {
+        name: "TestStorage0".to_string(),
+        prefix: "TestStorage0".to_string(),
+        modifier: StorageEntryModifier::Default,
+        ty: StorageEntryType::Plain(moment.clone()),
+        default: vec![112, 23, 0, 0, 0, 0, 0, 0],
+        documentation: vec!["Some Kind of docs".to_string()],
+    });
+
+    storage.push(StorageMetadata {
+        name: "TestStorage1".to_string(),
+        prefix: "TestStorage1".to_string(),
+        modifier: StorageEntryModifier::Default,
+        ty: StorageEntryType::Plain(usize_t),
+        default: vec![0, 0, 0, 0, 0, 0, 0, 0],
+        documentation: vec!["Some Kind of docs 2".to_string()],
+    });
+
+    storage.push(StorageMetadata {
+        name: "TestStorage2".to_string(),
+        prefix: "TestStorage2".to_string(),
+        modifier: StorageEntryModifier::Optional,
+        ty: StorageEntryType::Plain(moment),
+        default: vec![0, 0, 0, 0, 0, 0, 0, 0],
+        documentation: vec!["Some Kind of docs 2".to_string()],
+    });
+
+    storage.push(StorageMetadata {
+        name: "TestStorage3".to_string(),
+        prefix: "TestStorage3".to_string(),
+        modifier: StorageEntryModifier::Optional,
+        ty: StorageEntryType::Plain(precision),
+        default: vec![0, 0, 0, 0, 0, 0, 0, 0],
+        documentation: vec!["Some Kind of docs 3".to_string()],
+    });
+    storage
 }
 
-fn call_mock() -> HashMap<String, Vec<u8>> {
-    let mut map = HashMap::new();
-    map.insert("TestCall0".to_string(), vec![01, 02, 03, 04, 05]);
-    map.insert("TestCall1".to_string(), vec![11, 12, 13, 14, 15, 16, 17]);
-    map.insert(
-        "TestCall2".to_string(),
-        vec![21, 22, 23, 24, 25, 26, 27, 28, 29],
-    );
-    map.insert(
-        "TestCall3".to_string(),
-        vec![31, 32, 33, 34, 35, 36, 37, 38, 39],
-    );
-    map
+fn call_mock() -> Vec<CallMetadata> {
+    let calls = vec![
+        ("TestCall0", vec![("moment", "T::Moment")]),
+        ("TestCall1", vec![("amount", "Compact<u64>"), ("remark", "Vec<u8>")]),
+        ("TestCall2", vec![("precision", "F::Precision")]),
+        ("TestCall3", vec![]),
+    ];
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, args))| CallMetadata {
+            name: name.to_string(),
+            index: index as u8,
+            arguments: args
+                .into_iter()
+                .map(|(name, ty)| CallArgMetadata {
+                    name: name.to_string(),
+                    ty: ty.to_string(),
+                })
+                .collect(),
+            documentation: vec!["Some Kind of call docs".to_string()],
+        })
+        .collect()
 }
 
-fn event_mock() -> HashMap<u8, ModuleEventMetadata> {
-    let mut map = HashMap::new();
-
+fn event_mock() -> Vec<ModuleEventMetadata> {
     let event_arg_0 = EventArg::Primitive("TestEvent0".to_string());
     let event_arg_1 = EventArg::Primitive("TestEvent1".to_string());
     let event_arg_2 = EventArg::Primitive("TestEvent2".to_string());
 
-    let mut arguments = HashSet::new();
-    arguments.insert(event_arg_0);
-    arguments.insert(event_arg_1);
-    arguments.insert(event_arg_2);
-    let module_event_metadata = ModuleEventMetadata {
+    let arguments = vec![event_arg_0, event_arg_1, event_arg_2];
+    vec![ModuleEventMetadata {
         name: "TestEvent0".to_string(),
         arguments,
-    };
+        documentation: vec!["Some Kind of event docs".to_string()],
+    }]
+}
+
+fn constant_mock() -> Vec<ModuleConstantMetadata> {
+    vec![ModuleConstantMetadata {
+        name: "TestConstant0".to_string(),
+        ty: "T::Moment".to_string(),
+        value: vec![112, 23, 0, 0],
+        documentation: vec!["Some Kind of constant docs".to_string()],
+    }]
+}
+
+fn error_mock() -> Vec<ModuleErrorMetadata> {
+    (0 .. 3)
+        .map(|index| ModuleErrorMetadata {
+            name: format!("TestError{}", index),
+            index,
+            documentation: vec![format!("Some Kind of error docs {}", index)],
+        })
+        .collect()
+}
 
-    map.insert(0, module_event_metadata);
-    map
+fn system_event_mock() -> Vec<ModuleEventMetadata> {
+    let dispatch_info = EventArg::Primitive("DispatchInfo".to_string());
+    let dispatch_error = EventArg::Primitive("DispatchError".to_string());
+
+    vec![
+        ModuleEventMetadata {
+            name: "ExtrinsicSuccess".to_string(),
+            arguments: vec![dispatch_info.clone()],
+            documentation: vec![" An extrinsic completed successfully.".to_string()],
+        },
+        ModuleEventMetadata {
+            name: "ExtrinsicFailed".to_string(),
+            arguments: vec![dispatch_error, dispatch_info],
+            documentation: vec![" An extrinsic failed.".to_string()],
+        },
+    ]
 }
@@ -13,139 +13,159 @@
 //
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
-// Copyright 2019 Parity Technologies (UK) Ltd.
-// This file is part of substrate-desub.
-//
-// substrate-desub is free software: you can redistribute it and/or modify
-// it under the terms of the GNU General Public License as published by
-// the Free Software Foundation, either version 3 of the License, or
-// (at your option) any later version.
-//
-// substrate-desub is distributed in the hope that it will be useful,
-// but WITHOUT ANY WARRANTY; without even the implied warranty of
-// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
-// GNU General Public License for more details.
-//
-// You should have received a copy of the GNU General Public License
-// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
-use super::{
-    Error, EventArg, Metadata, ModuleEventMetadata, ModuleMetadata, StorageMetadata,
-};
+//! Conversion of V11 metadata, the latest version,
+//! and export of the unified model back to it
 
-use runtime_metadata_latest::{
-    DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
-    StorageEntryType, StorageHasher, META_RESERVED,
-};
-use std::{
-    collections::{HashMap, HashSet},
-    convert::TryFrom,
-    rc::Rc,
+use super::{
+    frame::{
+        latest::{self, RuntimeMetadata, RuntimeMetadataPrefixed, RuntimeMetadataV11},
+        META_RESERVED,
+    },
+    versions::{LatestMetadata, MetadataVersion},
+    Error, ExtrinsicMetadata, Metadata, ModuleMetadata,
 };
+use codec::Encode;
+use crate::prelude::*;
 
-impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
-    type Error = Error;
+impl MetadataVersion for RuntimeMetadataPrefixed {
+    const VERSION: u8 = 11;
 
-    fn try_from(metadata: RuntimeMetadataPrefixed) -> Result<Self, Self::Error> {
-        if metadata.0 != META_RESERVED {
-            // 'meta' warn endiannes
-            Err(Error::InvalidPrefix)?;
-        }
-        let meta = match metadata.1 {
-            RuntimeMetadata::V11(meta) => meta,
-            _ => Err(Error::InvalidVersion)?,
+    fn into_latest(self) -> Result<LatestMetadata, Error> {
+        let RuntimeMetadata::V11(meta) = self.1;
+        let extrinsic = ExtrinsicMetadata {
+            version: meta.extrinsic.version,
+            signed_extensions: meta.extrinsic.signed_extensions,
         };
-        let mut modules = HashMap::new();
-        let mut modules_by_event_index = HashMap::new();
-        let mut event_index = 0;
-        for (i, module) in convert(meta.modules)?.into_iter().enumerate() {
-            let module_name = convert(module.name.clone())?;
-            let module_metadata = convert_module(i, module)?;
-            // modules with no events have no corresponding definition in the
-            // top level enum
-            if !module_metadata.events.is_empty() {
-                modules_by_event_index.insert(event_index, module_name.clone());
-                event_index = event_index + 1;
-            }
-            modules.insert(module_name, Rc::new(module_metadata));
-        }
-        Ok(Metadata {
-            modules,
-            modules_by_event_index,
+        Ok(LatestMetadata {
+            modules: meta.modules,
+            extrinsic: Some(extrinsic),
         })
     }
 }
 
-fn convert<B: 'static, O: 'static>(dd: DecodeDifferent<B, O>) -> Result<O, Error> {
-    match dd {
-        DecodeDifferent::Decoded(value) => Ok(value),
-        _ => Err(Error::ExpectedDecoded),
+try_from_version!(RuntimeMetadataPrefixed);
+
+/// Re-encode the unified model in the latest metadata layout
+///
+/// Metadata from before V11 does not describe the extrinsic format,
+/// it is exported as version 4 without any signed extensions
+impl From<&Metadata> for RuntimeMetadataPrefixed {
+    fn from(metadata: &Metadata) -> Self {
+        let modules = metadata.modules().map(|m| export_module(m)).collect();
+        let extrinsic = metadata.extrinsic.clone().unwrap_or(ExtrinsicMetadata {
+            version: 4,
+            signed_extensions: Vec::new(),
+        });
+        let extrinsic = latest::ExtrinsicMetadata {
+            version: extrinsic.version,
+            signed_extensions: extrinsic.signed_extensions,
+        };
+        RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V11(RuntimeMetadataV11 { modules, extrinsic }),
+        )
     }
 }
 
-fn convert_module(
-    index: usize, module: runtime_metadata_latest::ModuleMetadata,
-) -> Result<ModuleMetadata, Error> {
-    let mut storage_map = HashMap::new();
-    if let Some(storage) = module.storage {
-        let storage = convert(storage)?;
-        let prefix = convert(storage.prefix)?;
-        for entry in convert(storage.entries)?.into_iter() {
-            let entry_name = convert(entry.name.clone())?;
-            let entry_prefix = format!("{} {}", prefix, entry_name);
-            let entry = convert_entry(entry_prefix, entry)?;
-            storage_map.insert(entry_name, entry);
-        }
-    }
-    let mut call_map = HashMap::new();
-    if let Some(calls) = module.calls {
-        for (index, call) in convert(calls)?.into_iter().enumerate() {
-            // HERE modify
-            let name = convert(call.name)?;
-            call_map.insert(name, vec![index as u8]);
-        }
-    }
-    let mut event_map = HashMap::new();
-    if let Some(events) = module.event {
-        for (index, event) in convert(events)?.into_iter().enumerate() {
-            event_map.insert(index as u8, convert_event(event)?);
-        }
+impl Metadata {
+    /// convert to the latest metadata layout
+    pub fn to_latest(&self) -> RuntimeMetadataPrefixed {
+        self.into()
     }
 
-    Ok(ModuleMetadata {
-        index: index as u8,
-        name: convert(module.name)?,
-        storage: storage_map,
-        calls: call_map,
-        events: event_map,
-    })
+    /// SCALE-encode in the latest metadata layout
+    pub fn encode_latest(&self) -> Vec<u8> {
+        self.to_latest().encode()
+    }
 }
 
-fn convert_event(
-    event: runtime_metadata_latest::EventMetadata,
-) -> Result<ModuleEventMetadata, Error> {
-    let name = convert(event.name)?;
-    let mut arguments = HashSet::new();
-    for arg in convert(event.arguments)? {
-        let arg = arg.parse::<EventArg>()?;
-        arguments.insert(arg);
+fn export_module(module: &ModuleMetadata) -> latest::ModuleMetadata {
+    let storage = module.storage_prefix.as_ref().map(|prefix| {
+        let entries = module
+            .storage
+            .iter()
+            .map(|entry| latest::StorageEntryMetadata {
+                name: entry.name.clone(),
+                modifier: entry.modifier.clone(),
+                ty: entry.ty.clone(),
+                default: entry.default.clone(),
+                documentation: entry.documentation.clone(),
+            })
+            .collect();
+        latest::StorageMetadata {
+            prefix: prefix.clone(),
+            entries,
+        }
+    });
+    let calls = module.call_index.map(|_| {
+        module
+            .calls
+            .iter()
+            .map(|call| latest::FunctionMetadata {
+                name: call.name.clone(),
+                arguments: call
+                    .arguments
+                    .iter()
+                    .map(|arg| latest::FunctionArgumentMetadata {
+                        name: arg.name.clone(),
+                        ty: arg.ty.clone(),
+                    })
+                    .collect(),
+                documentation: call.documentation.clone(),
+            })
+            .collect()
+    });
+    let event = module.event_index.map(|_| {
+        module
+            .events
+            .iter()
+            .map(|event| latest::EventMetadata {
+                name: event.name.clone(),
+                arguments: event.arguments.iter().map(|arg| arg.to_string()).collect(),
+                documentation: event.documentation.clone(),
+            })
+            .collect()
+    });
+    let constants = module
+        .constants
+        .iter()
+        .map(|constant| latest::ModuleConstantMetadata {
+            name: constant.name.clone(),
+            ty: constant.ty.clone(),
+            value: constant.value.clone(),
+            documentation: constant.documentation.clone(),
+        })
+        .collect();
+    let errors = module
+        .errors
+        .iter()
+        .map(|error| latest::ErrorMetadata {
+            name: error.name.clone(),
+            documentation: error.documentation.clone(),
+        })
+        .collect();
+
+    latest::ModuleMetadata {
+        name: module.name.clone(),
+        storage,
+        calls,
+        event,
+        constants,
+        errors,
     }
-    Ok(ModuleEventMetadata { name, arguments })
 }
 
-fn convert_entry(
-    prefix: String, entry: runtime_metadata_latest::StorageEntryMetadata,
-) -> Result<StorageMetadata, Error> {
-    let default = convert(entry.default)?;
-    let documentation = convert(entry.documentation)?;
-    Ok(StorageMetadata {
-        prefix,
-        modifier: entry.modifier,
-        ty: entry.ty,
-        default,
-        documentation: documentation
-            .iter()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>(),
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_suite;
+
+    #[test]
+    fn should_export_to_latest() {
+        let meta = Metadata::from_bytes(&test_suite::runtime_v9()).unwrap();
+        let latest = Metadata::from_bytes(&meta.encode_latest()).unwrap();
+        assert!(meta.modules().eq(latest.modules()));
+        assert_eq!(latest.extrinsic().unwrap().version, 4);
+    }
 }
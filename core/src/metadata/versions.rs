@@ -14,12 +14,542 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
-/// A generic trait that all metadata version must implement
-/// in order to be converted to the "Metadata" struct
-use super::{Metadata, ModuleMetadata};
+//! Conversion of every version of the runtime metadata to the unified
+//! `Metadata` struct
+//!
+//! Each metadata version implements `MetadataVersion`, which upgrades its
+//! modules to the latest layout. Decoding, checking the `meta` prefix and
+//! building the unified model from the latest layout are shared by every
+//! version, as are computing module indices and parsing event arguments.
+//!
+//! Converters for custom or forked metadata formats are registered with
+//! `Converters`, without the need to patch `Metadata::new`:
+//!
+//! ```ignore
+//! let mut converters = Converters::default();
+//! converters.register::<MyForkedMetadata>();
+//! let metadata = converters.convert(&bytes)?;
+//! ```
 
-use codec::Decode;
-use runtime_metadata_latest::{
-    DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
-    StorageEntryType, StorageHasher, META_RESERVED,
+use super::{
+    CallArgMetadata, CallMetadata, Error, EventArg, ExtrinsicMetadata, Metadata,
+    ModuleConstantMetadata, ModuleErrorMetadata, ModuleEventMetadata, ModuleMetadata,
+    StorageMetadata,
 };
+use super::frame::{self, latest, META_RESERVED};
+use codec::Decode;
+use crate::prelude::*;
+use core::{convert::TryFrom, fmt};
+
+/// A version of the runtime metadata which can be converted to the unified
+/// `Metadata` struct
+///
+/// The metadata is decoded from SCALE, including its `meta` prefix, once the
+/// prefix has been checked
+pub trait MetadataVersion: Decode {
+    /// the version byte following the `meta` prefix of the encoded metadata
+    const VERSION: u8;
+
+    /// upgrade the metadata to the latest layout
+    fn into_latest(self) -> Result<LatestMetadata, Error>;
+}
+
+/// Metadata of any version upgraded to the latest layout
+pub struct LatestMetadata {
+    /// modules in the order they are declared in the runtime
+    pub modules: Vec<latest::ModuleMetadata>,
+    /// the extrinsic format, if it is part of the metadata
+    pub extrinsic: Option<ExtrinsicMetadata>,
+}
+
+type ConvertFn = fn(&[u8]) -> Result<Metadata, Error>;
+
+/// Converters for every supported metadata version, by version byte
+#[derive(Clone)]
+pub struct Converters {
+    converters: HashMap<u8, ConvertFn>,
+}
+
+impl Default for Converters {
+    /// converters for V7 to V11
+    fn default() -> Self {
+        let mut converters = Converters::empty();
+        converters.register::<frame::v07::RuntimeMetadataPrefixed>();
+        converters.register::<frame::v08::RuntimeMetadataPrefixed>();
+        converters.register::<frame::v09::RuntimeMetadataPrefixed>();
+        converters.register::<frame::v10::RuntimeMetadataPrefixed>();
+        converters.register::<frame::v11::RuntimeMetadataPrefixed>();
+        converters
+    }
+}
+
+impl fmt::Debug for Converters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut versions = self.converters.keys().collect::<Vec<_>>();
+        versions.sort();
+        f.debug_struct("Converters")
+            .field("versions", &versions)
+            .finish()
+    }
+}
+
+impl Converters {
+    /// no converters at all
+    pub fn empty() -> Self {
+        Converters {
+            converters: HashMap::new(),
+        }
+    }
+
+    /// register a converter for `T::VERSION`,
+    /// replacing any converter previously registered for that version
+    pub fn register<T: MetadataVersion>(&mut self) {
+        self.converters.insert(T::VERSION, convert_version::<T>);
+    }
+
+    pub fn supports(&self, version: u8) -> bool {
+        self.converters.contains_key(&version)
+    }
+
+    /// convert SCALE-encoded prefixed metadata of any registered version
+    pub fn convert(&self, bytes: &[u8]) -> Result<Metadata, Error> {
+        // Runtime metadata is a tuple struct with the following fields:
+        // RuntimeMetadataPrefixed(u32, RuntimeMetadata)
+        // this means when it's SCALE encoded, the first four bytes
+        // are the 'u32' prefix, and since `RuntimeMetadata` is an enum,
+        // the first byte is the index of the enum item.
+        // Since RuntimeMetadata is versioned starting from 0, this also corresponds to
+        // the Metadata version
+        if bytes.len() < 5 {
+            Err(Error::TooShort(bytes.len()))?;
+        }
+        let version = bytes[4];
+        match self.converters.get(&version) {
+            Some(convert) => convert(bytes),
+            None => Err(Error::UnsupportedVersion(version)),
+        }
+    }
+}
+
+fn convert_version<T: MetadataVersion>(bytes: &[u8]) -> Result<Metadata, Error> {
+    if bytes.get(.. 4) != Some(&META_RESERVED.to_le_bytes()[..]) {
+        // 'meta' warn endiannes
+        Err(Error::InvalidPrefix)?;
+    }
+    let metadata = T::decode(&mut &bytes[..])
+        .map_err(|e| Error::Decode(T::VERSION, format!("{:?}", e)))?;
+    Metadata::from_latest(metadata.into_latest()?)
+}
+
+impl Metadata {
+    /// build the unified model from metadata in the latest layout
+    pub fn from_latest(metadata: LatestMetadata) -> Result<Self, Error> {
+        let mut modules = Vec::new();
+        let mut event_index = 0;
+        let mut call_index = 0;
+        for (i, module) in metadata.modules.into_iter().enumerate() {
+            // modules with no events have no corresponding definition in the
+            // top level enum
+            let module_event_index = match module.event {
+                Some(_) => {
+                    event_index += 1;
+                    Some(to_index(event_index - 1, "modules with events")?)
+                }
+                None => None,
+            };
+            // same goes for modules without calls and the outer `Call` enum
+            let module_call_index = match module.calls {
+                Some(_) => {
+                    call_index += 1;
+                    Some(to_index(call_index - 1, "modules with calls")?)
+                }
+                None => None,
+            };
+            modules.push(convert_module(
+                i,
+                module_call_index,
+                module_event_index,
+                module,
+            )?);
+        }
+        let mut unified = Metadata::from_modules(modules);
+        unified.extrinsic = metadata.extrinsic;
+        Ok(unified)
+    }
+}
+
+/// Implement `TryFrom<$ty> for Metadata`, where `$ty` implements
+/// `MetadataVersion`
+macro_rules! try_from_version {
+    ($ty:ty) => {
+        impl ::core::convert::TryFrom<$ty> for $crate::metadata::Metadata {
+            type Error = $crate::metadata::Error;
+
+            fn try_from(metadata: $ty) -> ::core::result::Result<Self, Self::Error> {
+                use $crate::metadata::MetadataVersion;
+                Self::from_latest(metadata.into_latest()?)
+            }
+        }
+    };
+}
+
+/// Implement `MetadataVersion` for the prefixed metadata of an older
+/// version, which only needs its modules upgraded to the latest layout
+///
+/// The module layout is the same from V7 to V10, except for errors which
+/// are part of the metadata since V8 (pass `errors`), and the storage
+/// hashers each version supports. Hashers keep their name in the latest
+/// layout:
+///
+/// ```ignore
+/// modules_to_latest!(v10::V10 = 10, errors, hashers: [Blake2_128, Twox128]);
+/// ```
+macro_rules! modules_to_latest {
+    (
+        $krate:ident::$variant:ident = $version:literal, errors,
+        hashers: [$($hasher:ident),* $(,)?]
+    ) => {
+        modules_to_latest!(
+            @impl $krate, $variant, $version, with_errors, $($hasher),*
+        );
+    };
+    (
+        $krate:ident::$variant:ident = $version:literal,
+        hashers: [$($hasher:ident),* $(,)?]
+    ) => {
+        modules_to_latest!(
+            @impl $krate, $variant, $version, without_errors, $($hasher),*
+        );
+    };
+    (@errors with_errors, $module:ident) => {
+        $module
+            .errors
+            .into_iter()
+            .map(|error| $crate::metadata::frame::latest::ErrorMetadata {
+                name: error.name,
+                documentation: error.documentation,
+            })
+            .collect()
+    };
+    (@errors without_errors, $module:ident) => {
+        $crate::prelude::Vec::new()
+    };
+    (
+        @impl $krate:ident, $variant:ident, $version:literal, $errors:ident,
+        $($hasher:ident),*
+    ) => {
+        impl $crate::metadata::MetadataVersion
+            for $crate::metadata::frame::$krate::RuntimeMetadataPrefixed
+        {
+            const VERSION: u8 = $version;
+
+            fn into_latest(
+                self,
+            ) -> ::core::result::Result<
+                $crate::metadata::LatestMetadata,
+                $crate::metadata::Error,
+            > {
+                use $crate::metadata::frame::$krate::RuntimeMetadata;
+
+                let RuntimeMetadata::$variant(meta) = self.1;
+                ::core::result::Result::Ok($crate::metadata::LatestMetadata {
+                    modules: meta.modules.into_iter().map(convert_module).collect(),
+                    extrinsic: ::core::option::Option::None,
+                })
+            }
+        }
+
+        try_from_version!($crate::metadata::frame::$krate::RuntimeMetadataPrefixed);
+
+        fn convert_module(
+            module: $crate::metadata::frame::$krate::ModuleMetadata,
+        ) -> $crate::metadata::frame::latest::ModuleMetadata {
+            use $crate::metadata::frame::latest;
+
+            let storage = module.storage.map(|storage| latest::StorageMetadata {
+                prefix: storage.prefix,
+                entries: storage.entries.into_iter().map(convert_entry).collect(),
+            });
+            let calls = module
+                .calls
+                .map(|calls| calls.into_iter().map(convert_call).collect());
+            let event = module.event.map(|events| {
+                events
+                    .into_iter()
+                    .map(|event| latest::EventMetadata {
+                        name: event.name,
+                        arguments: event.arguments,
+                        documentation: event.documentation,
+                    })
+                    .collect()
+            });
+            let constants = module
+                .constants
+                .into_iter()
+                .map(|constant| latest::ModuleConstantMetadata {
+                    name: constant.name,
+                    ty: constant.ty,
+                    value: constant.value,
+                    documentation: constant.documentation,
+                })
+                .collect();
+            let errors = modules_to_latest!(@errors $errors, module);
+
+            latest::ModuleMetadata {
+                name: module.name,
+                storage,
+                calls,
+                event,
+                constants,
+                errors,
+            }
+        }
+
+        fn convert_call(
+            call: $crate::metadata::frame::$krate::FunctionMetadata,
+        ) -> $crate::metadata::frame::latest::FunctionMetadata {
+            use $crate::metadata::frame::latest;
+
+            let arguments = call
+                .arguments
+                .into_iter()
+                .map(|arg| latest::FunctionArgumentMetadata {
+                    name: arg.name,
+                    ty: arg.ty,
+                })
+                .collect();
+            latest::FunctionMetadata {
+                name: call.name,
+                arguments,
+                documentation: call.documentation,
+            }
+        }
+
+        fn convert_entry(
+            entry: $crate::metadata::frame::$krate::StorageEntryMetadata,
+        ) -> $crate::metadata::frame::latest::StorageEntryMetadata {
+            use $crate::metadata::frame::{$krate, latest};
+
+            let modifier = match entry.modifier {
+                $krate::StorageEntryModifier::Optional => {
+                    latest::StorageEntryModifier::Optional
+                }
+                $krate::StorageEntryModifier::Default => {
+                    latest::StorageEntryModifier::Default
+                }
+            };
+            let ty = match entry.ty {
+                $krate::StorageEntryType::Plain(value) => {
+                    latest::StorageEntryType::Plain(value)
+                }
+                $krate::StorageEntryType::Map {
+                    hasher,
+                    key,
+                    value,
+                    is_linked,
+                } => latest::StorageEntryType::Map {
+                    hasher: convert_hasher(hasher),
+                    key,
+                    value,
+                    is_linked,
+                },
+                $krate::StorageEntryType::DoubleMap {
+                    hasher,
+                    key1,
+                    key2,
+                    value,
+                    key2_hasher,
+                } => latest::StorageEntryType::DoubleMap {
+                    hasher: convert_hasher(hasher),
+                    key1,
+                    key2,
+                    value,
+                    key2_hasher: convert_hasher(key2_hasher),
+                },
+            };
+            latest::StorageEntryMetadata {
+                name: entry.name,
+                modifier,
+                ty,
+                default: entry.default,
+                documentation: entry.documentation,
+            }
+        }
+
+        /// convert a storage hasher to its equivalent in the latest metadata
+        fn convert_hasher(
+            hasher: $crate::metadata::frame::$krate::StorageHasher,
+        ) -> $crate::metadata::frame::latest::StorageHasher {
+            use $crate::metadata::frame::{$krate, latest};
+
+            match hasher {
+                $($krate::StorageHasher::$hasher => latest::StorageHasher::$hasher,)*
+            }
+        }
+    };
+}
+
+fn convert_module(
+    index: usize, call_index: Option<u8>, event_index: Option<u8>,
+    module: latest::ModuleMetadata,
+) -> Result<ModuleMetadata, Error> {
+    let mut storage = Vec::new();
+    let mut storage_prefix = None;
+    if let Some(module_storage) = module.storage {
+        let prefix = module_storage.prefix;
+        for entry in module_storage.entries.into_iter() {
+            let entry_prefix = format!("{} {}", prefix, entry.name);
+            storage.push(convert_entry(entry_prefix, entry));
+        }
+        storage_prefix = Some(prefix);
+    }
+    let mut calls = Vec::new();
+    if let Some(module_calls) = module.calls {
+        for (index, call) in module_calls.into_iter().enumerate() {
+            let arguments = call
+                .arguments
+                .into_iter()
+                .map(|arg| CallArgMetadata {
+                    name: arg.name,
+                    ty: arg.ty,
+                })
+                .collect();
+            calls.push(CallMetadata {
+                name: call.name,
+                index: to_index(index, "calls in a module")?,
+                arguments,
+                documentation: call.documentation,
+            });
+        }
+    }
+    let mut events = Vec::new();
+    if let Some(module_events) = module.event {
+        for event in module_events.into_iter() {
+            events.push(convert_event(event)?);
+        }
+    }
+
+    let errors = module
+        .errors
+        .into_iter()
+        .enumerate()
+        .map(|(index, error)| convert_error(index, error))
+        .collect::<Result<_, _>>()?;
+
+    let constants = module.constants.into_iter().map(convert_constant).collect();
+
+    Ok(ModuleMetadata {
+        index: to_index(index, "modules")?,
+        call_index,
+        event_index,
+        name: module.name,
+        storage_prefix,
+        storage,
+        calls,
+        events,
+        errors,
+        constants,
+    })
+}
+
+fn convert_error(
+    index: usize, error: latest::ErrorMetadata,
+) -> Result<ModuleErrorMetadata, Error> {
+    Ok(ModuleErrorMetadata {
+        name: error.name,
+        index: to_index(index, "errors in a module")?,
+        documentation: error.documentation,
+    })
+}
+
+/// indices of modules, calls and errors are encoded as a single byte
+fn to_index(index: usize, items: &'static str) -> Result<u8, Error> {
+    u8::try_from(index).map_err(|_| Error::TooMany(items))
+}
+
+fn convert_event(event: latest::EventMetadata) -> Result<ModuleEventMetadata, Error> {
+    let mut arguments = Vec::new();
+    for arg in event.arguments {
+        let arg = arg.parse::<EventArg>()?;
+        arguments.push(arg);
+    }
+    Ok(ModuleEventMetadata {
+        name: event.name,
+        arguments,
+        documentation: event.documentation,
+    })
+}
+
+fn convert_constant(constant: latest::ModuleConstantMetadata) -> ModuleConstantMetadata {
+    ModuleConstantMetadata {
+        name: constant.name,
+        ty: constant.ty,
+        value: constant.value,
+        documentation: constant.documentation,
+    }
+}
+
+fn convert_entry(prefix: String, entry: latest::StorageEntryMetadata) -> StorageMetadata {
+    StorageMetadata {
+        name: entry.name,
+        prefix,
+        modifier: entry.modifier,
+        ty: entry.ty,
+        default: entry.default,
+        documentation: entry.documentation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_suite;
+
+    /// V11 metadata with a custom version byte
+    #[derive(Decode)]
+    struct ForkedMetadata(u32, u8, latest::RuntimeMetadataV11);
+
+    impl MetadataVersion for ForkedMetadata {
+        const VERSION: u8 = 200;
+
+        fn into_latest(self) -> Result<LatestMetadata, Error> {
+            let meta = latest::RuntimeMetadata::V11(self.2);
+            latest::RuntimeMetadataPrefixed(self.0, meta).into_latest()
+        }
+    }
+
+    #[test]
+    fn should_register_converter() {
+        let meta = Metadata::from_bytes(&test_suite::runtime_v9()).unwrap();
+        let mut forked = meta.encode_latest();
+        forked[4] = 200;
+
+        let mut converters = Converters::default();
+        assert!(converters.convert(&forked).is_err());
+        converters.register::<ForkedMetadata>();
+        let converted = converters.convert(&forked).unwrap();
+        assert!(meta.modules().eq(converted.modules()));
+    }
+
+    #[test]
+    fn should_not_truncate_module_indices() {
+        let module = |i| latest::ModuleMetadata {
+            name: format!("Module{}", i),
+            storage: None,
+            calls: None,
+            event: Some(Vec::new()),
+            constants: Vec::new(),
+            errors: Vec::new(),
+        };
+        let latest = |count| LatestMetadata {
+            modules: (0 .. count).map(module).collect(),
+            extrinsic: None,
+        };
+        let meta = Metadata::from_latest(latest(256)).unwrap();
+        assert_eq!(meta.module_by_event_index(255).unwrap().name(), "Module255");
+        match Metadata::from_latest(latest(257)) {
+            Err(Error::TooMany(_)) => (),
+            _ => panic!("the index of the 257th module must not fit in a byte"),
+        }
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Version of a runtime, as returned by `state_getRuntimeVersion`
+//!
+//! SCALE-compatible with `RuntimeVersion` of `sp-version`
+
+use codec::{Decode, Encode};
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// identifier of a runtime API, the first 8 bytes of the blake2 hash of its name
+pub type ApiId = [u8; 8];
+
+#[derive(Clone, Debug, PartialEq, Eq, Default, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+    /// identifies the different runtimes, IE: `polkadot` or `kusama`
+    pub spec_name: String,
+    /// name of the implementation of the spec
+    pub impl_name: String,
+    /// version of the authorship interface
+    pub authoring_version: u32,
+    /// version of the runtime specification, types are registered per spec version
+    pub spec_version: u32,
+    /// version of the implementation of the specification
+    pub impl_version: u32,
+    /// runtime APIs and their versions
+    pub apis: Vec<(ApiId, u32)>,
+}
@@ -14,11 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
+use crate::decoder::Decoder;
 use crate::metadata::test_suite::test_metadata;
-use runtime_version::RuntimeVersion;
+use crate::RuntimeVersion;
 
 use std::{
-    borrow::Cow,
     io::{self, prelude::*},
     fs::File
 };
@@ -31,10 +31,19 @@ pub fn mock_runtime(num: u32) -> RuntimeVersion {
         authoring_version: num,
         spec_version: num,
         impl_version: num,
-        apis: Cow::from(Vec::new()),
+        apis: Vec::new(),
     }
 }
 
+/// A decoder with the test metadata registered for spec versions `0 .. n`
+pub fn decoder_with_versions(n: u32) -> Decoder {
+    let mut decoder = Decoder::new();
+    for spec in 0 .. n {
+        decoder.register_version(test_metadata(), mock_runtime(spec));
+    }
+    decoder
+}
+
 /// Get some runtime metadata from KusamaCC3 around block 361,0000
 /// Block hash 0x627a6a8e7698dd360bd44e7816e7f8c5321fa31e0a3f39324d93ec5716a57fb5
 ///
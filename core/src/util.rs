@@ -0,0 +1,34 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers shared by the decoder and the metadata
+
+use crate::prelude::*;
+use serde::Serializer;
+
+/// encode bytes as a `0x`-prefixed hex string
+pub(crate) fn to_hex<B: AsRef<[u8]>>(bytes: B) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// serde helper to serialize bytes as a `0x`-prefixed hex string
+pub(crate) fn serialize_hex<S, B>(bytes: &B, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    B: AsRef<[u8]>,
+{
+    serializer.serialize_str(&to_hex(bytes))
+}
@@ -0,0 +1,88 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Helpers to describe the types of a runtime
+//!
+//! Types are written the same way they appear in the metadata,
+//! and parsed into the markers the decoder understands
+
+use core::{
+    decoder::{Decoder, EnumVariant, RustTypeMarker, SpecRange, StructField},
+    Error, RuntimeVersion,
+};
+
+/// Type definitions of a module, by the name they appear with in the metadata
+pub type ModuleTypes = Vec<(&'static str, RustTypeMarker)>;
+
+/// Parse a type as it is written in the metadata
+///
+/// # Panics
+///
+/// panics if `def` is not a valid type.
+/// Definitions are static, so this is always a bug in the definition
+pub fn ty(def: &str) -> RustTypeMarker {
+    def.parse()
+        .unwrap_or_else(|e| panic!("invalid type definition `{}`: {}", def, e))
+}
+
+/// A struct made of `(name, type)` fields, in the order they are encoded
+pub fn structure(fields: &[(&str, &str)]) -> RustTypeMarker {
+    RustTypeMarker::Struct(
+        fields
+            .iter()
+            .map(|(name, def)| StructField::new(*name, ty(def)))
+            .collect(),
+    )
+}
+
+/// An enum made of `(name, type)` variants, in the order of their index
+///
+/// variants without data have a type of `()`
+pub fn enumeration(variants: &[(&str, &str)]) -> RustTypeMarker {
+    RustTypeMarker::Enum(
+        variants
+            .iter()
+            .map(|(name, def)| EnumVariant::new(*name, ty(def)))
+            .collect(),
+    )
+}
+
+/// An enum of which no variant holds any data
+pub fn unit_enum(variants: &[&str]) -> RustTypeMarker {
+    RustTypeMarker::Enum(variants.iter().map(|v| EnumVariant::unit(*v)).collect())
+}
+
+/// Register every type of `types` for `module`
+pub fn register_module(
+    decoder: &mut Decoder, version: &RuntimeVersion, module: &str,
+    types: &[(&str, RustTypeMarker)],
+) -> Result<(), Error> {
+    for (name, marker) in types {
+        decoder.register_marker(version, module, *name, marker.clone())?;
+    }
+    Ok(())
+}
+
+/// Register every type of `types` for `module` in every runtime version of
+/// `specs`
+pub fn register_module_range(
+    decoder: &mut Decoder, specs: SpecRange, module: &str,
+    types: &[(&str, RustTypeMarker)],
+) {
+    for (name, marker) in types {
+        decoder.register_marker_range(specs, module, *name, marker.clone());
+    }
+}
@@ -0,0 +1,868 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Type definitions of the FRAME modules
+//!
+//! Runtimes built with FRAME share the primitive types of
+//! `node-primitives` (`T::AccountId`, `T::Balance`, ...), which are registered
+//! for every module. Types which are specific to a module are registered only
+//! for modules of that name. Runtimes add their own types on top, such as
+//! their session keys
+
+use crate::definitions::{
+    enumeration, register_module, register_module_range, structure, ty, unit_enum,
+    ModuleTypes,
+};
+use core::{
+    decoder::{dispatch_error, Decoder, RustTypeMarker, SpecRange},
+    Error, RuntimeVersion,
+};
+
+/// Register the types of FRAME for the runtime at `version`
+///
+/// `modules` are the names of the modules in the metadata of that runtime.
+/// Types returned by `runtime_types` for a module are registered last,
+/// and take precedence over the types of FRAME
+pub fn register_types<F>(
+    decoder: &mut Decoder, version: &RuntimeVersion, modules: &[String], runtime_types: F,
+) -> Result<(), Error>
+where
+    F: Fn(&str) -> Option<ModuleTypes>,
+{
+    let primitives = primitives();
+    for module in modules {
+        register_module(decoder, version, module, &primitives)?;
+        if let Some(types) = module_types(module) {
+            register_module(decoder, version, module, &types)?;
+        }
+        if let Some(types) = runtime_types(module) {
+            register_module(decoder, version, module, &types)?;
+        }
+    }
+
+    decoder.register_runtime_marker(
+        version,
+        "Address",
+        RustTypeMarker::GenericAddress,
+    )?;
+    decoder.register_runtime_marker(version, "Signature", signature())?;
+    decoder.register_runtime_marker(version, "SignedExtra", signed_extra())?;
+    Ok(())
+}
+
+/// Register the types of FRAME for every runtime version in `specs`
+///
+/// `modules` are the names of every module which is part of any runtime in
+/// `specs`. Like `register_types`, types returned by `runtime_types` take
+/// precedence over the types of FRAME
+pub fn register_types_range<F>(
+    decoder: &mut Decoder, specs: SpecRange, modules: &[&str], runtime_types: F,
+) where
+    F: Fn(&str) -> Option<ModuleTypes>,
+{
+    let primitives = primitives();
+    for &module in modules {
+        register_module_range(decoder, specs, module, &primitives);
+        if let Some(types) = module_types(module) {
+            register_module_range(decoder, specs, module, &types);
+        }
+        if let Some(types) = runtime_types(module) {
+            register_module_range(decoder, specs, module, &types);
+        }
+    }
+
+    decoder.register_runtime_marker_range(
+        specs,
+        "Address",
+        RustTypeMarker::GenericAddress,
+    );
+    decoder.register_runtime_marker_range(specs, "Signature", signature());
+    decoder.register_runtime_marker_range(specs, "SignedExtra", signed_extra());
+}
+
+/// The keys of the session module, one public key for every name in `keys`,
+/// in the order they are declared in the runtime
+pub fn session_keys(keys: &[&str]) -> ModuleTypes {
+    let fields = keys.iter().map(|k| (*k, "AuthorityId")).collect::<Vec<_>>();
+    let keys = structure(&fields);
+    vec![("T::Keys", keys.clone()), ("Keys", keys)]
+}
+
+/// types specific to a module of FRAME
+fn module_types(module: &str) -> Option<ModuleTypes> {
+    let types = match module {
+        "System" => system(),
+        "Babe" => babe(),
+        "Balances" => balances(),
+        "TransactionPayment" => vec![("Multiplier", RustTypeMarker::I64)],
+        "Authorship" => authorship(),
+        "Staking" => staking(),
+        "Offences" => offences(),
+        "Session" => session(),
+        "Grandpa" => grandpa(),
+        "ImOnline" => im_online(),
+        "Democracy" => democracy(),
+        "Council" | "TechnicalCommittee" => collective(),
+        "TechnicalMembership" => membership(),
+        "Treasury" => treasury(),
+        "Contracts" => contracts(),
+        "Vesting" => vesting(),
+        "Identity" => identity(),
+        "Society" => society(),
+        "Recovery" => recovery(),
+        "Utility" => utility(),
+        _ => return None,
+    };
+    Some(types)
+}
+
+/// `MultiSignature`
+fn signature() -> RustTypeMarker {
+    enumeration(&[
+        ("Ed25519", "H512"),
+        ("Sr25519", "H512"),
+        ("Ecdsa", "[u8; 65]"),
+    ])
+}
+
+/// The signed extensions which are encoded in an extrinsic
+///
+/// Only `CheckEra`, `CheckNonce` and `ChargeTransactionPayment` encode any
+/// data, every other extension (IE: `CheckVersion`, `CheckWeight`) is
+/// skipped
+fn signed_extra() -> RustTypeMarker {
+    structure(&[
+        ("era", "Era"),
+        ("nonce", "Compact<u32>"),
+        ("tip", "Compact<u128>"),
+    ])
+}
+
+/// types every module of the runtime is configured with
+fn primitives() -> ModuleTypes {
+    let account_id = ty("[u8; 32]");
+    let balance = RustTypeMarker::U128;
+    vec![
+        ("T::AccountId", account_id.clone()),
+        ("AccountId", account_id),
+        ("T::Balance", balance.clone()),
+        ("Balance", balance.clone()),
+        ("BalanceOf<T>", balance.clone()),
+        ("BalanceOf<T, I>", balance),
+        ("T::BlockNumber", RustTypeMarker::U32),
+        ("BlockNumber", RustTypeMarker::U32),
+        ("T::Hash", ty("H256")),
+        ("Hash", ty("H256")),
+        ("T::Index", RustTypeMarker::U32),
+        ("Index", RustTypeMarker::U32),
+        ("T::AccountIndex", RustTypeMarker::U32),
+        ("AccountIndex", RustTypeMarker::U32),
+        ("T::Moment", RustTypeMarker::U64),
+        ("Moment", RustTypeMarker::U64),
+        ("MomentOf<T>", RustTypeMarker::U64),
+        (
+            "<T::Lookup as StaticLookup>::Source",
+            RustTypeMarker::GenericAddress,
+        ),
+        ("T::Call", RustTypeMarker::Call),
+        ("<T as Trait>::Call", RustTypeMarker::Call),
+        ("T::Proposal", RustTypeMarker::Call),
+        ("<T as Trait>::Proposal", RustTypeMarker::Call),
+        ("<T as Trait<I>>::Proposal", RustTypeMarker::Call),
+        ("Weight", RustTypeMarker::U32),
+        ("Perbill", RustTypeMarker::U32),
+        ("Permill", RustTypeMarker::U32),
+        ("Percent", RustTypeMarker::U8),
+        ("SessionIndex", RustTypeMarker::U32),
+        ("AuthorityId", ty("[u8; 32]")),
+        ("ModuleId", ty("[u8; 8]")),
+        ("DispatchError", dispatch_error()),
+        (
+            "DispatchResult",
+            RustTypeMarker::Result(
+                Box::new(RustTypeMarker::Null),
+                Box::new(dispatch_error()),
+            ),
+        ),
+    ]
+}
+
+fn system() -> ModuleTypes {
+    let mut types = vec![
+        ("EventIndex", RustTypeMarker::U32),
+        ("Key", ty("Vec<u8>")),
+        ("KeyValue", ty("(Vec<u8>, Vec<u8>)")),
+        ("RefCount", RustTypeMarker::U8),
+        (
+            "AccountInfo<T::Index, T::AccountData>",
+            structure(&[
+                ("nonce", "Index"),
+                ("refcount", "RefCount"),
+                ("data", "AccountData"),
+            ]),
+        ),
+        ("DispatchClass", unit_enum(&["Normal", "Operational"])),
+        (
+            "DispatchInfo",
+            structure(&[
+                ("weight", "Weight"),
+                ("class", "DispatchClass"),
+                ("pays_fee", "bool"),
+            ]),
+        ),
+        (
+            "Phase",
+            enumeration(&[
+                ("ApplyExtrinsic", "u32"),
+                ("Finalization", "()"),
+                ("Initialization", "()"),
+            ]),
+        ),
+        (
+            "LastRuntimeUpgradeInfo",
+            structure(&[("spec_version", "Compact<u32>"), ("spec_name", "Vec<u8>")]),
+        ),
+    ];
+    types.extend(account_data());
+    types.extend(digest());
+    types
+}
+
+/// the balances held by an account, as part of the `AccountInfo` of the system
+/// module
+fn account_data() -> ModuleTypes {
+    let data = structure(&[
+        ("free", "Balance"),
+        ("reserved", "Balance"),
+        ("misc_frozen", "Balance"),
+        ("fee_frozen", "Balance"),
+    ]);
+    vec![
+        ("AccountData", data.clone()),
+        ("T::AccountData", data.clone()),
+        ("AccountData<T::Balance>", data),
+    ]
+}
+
+/// the digest of a header
+fn digest() -> ModuleTypes {
+    let item = enumeration(&[
+        ("Other", "Vec<u8>"),
+        ("AuthoritiesChange", "Vec<AuthorityId>"),
+        ("ChangesTrieRoot", "Hash"),
+        ("SealV0", "(u64, H512)"),
+        ("Consensus", "([u8; 4], Vec<u8>)"),
+        ("Seal", "([u8; 4], Vec<u8>)"),
+        ("PreRuntime", "([u8; 4], Vec<u8>)"),
+        ("ChangesTrieSignal", "ChangesTrieSignal"),
+    ]);
+    vec![
+        ("DigestItem", item),
+        (
+            "ChangesTrieSignal",
+            enumeration(&[("NewConfiguration", "Option<ChangesTrieConfiguration>")]),
+        ),
+        (
+            "ChangesTrieConfiguration",
+            structure(&[("digest_interval", "u32"), ("digest_levels", "u32")]),
+        ),
+        ("DigestOf<T>", ty("Vec<DigestItem>")),
+        ("Digest", ty("Vec<DigestItem>")),
+    ]
+}
+
+fn babe() -> ModuleTypes {
+    vec![
+        ("BabeAuthorityWeight", RustTypeMarker::U64),
+        ("schnorrkel::Randomness", ty("[u8; 32]")),
+        ("Randomness", ty("[u8; 32]")),
+        ("MaybeVrf", ty("Option<[u8; 32]>")),
+    ]
+}
+
+fn balances() -> ModuleTypes {
+    let mut types = vec![
+        ("LockIdentifier", ty("[u8; 8]")),
+        ("Reasons", unit_enum(&["Fee", "Misc", "All"])),
+        (
+            "BalanceLock<T::Balance>",
+            structure(&[
+                ("id", "LockIdentifier"),
+                ("amount", "Balance"),
+                ("reasons", "Reasons"),
+            ]),
+        ),
+        ("Releases", unit_enum(&["V1_0_0", "V2_0_0"])),
+        ("Status", unit_enum(&["Free", "Reserved"])),
+        // before balances were moved into the `AccountInfo` of the system
+        // module
+        ("WithdrawReasons", RustTypeMarker::U8),
+        (
+            "BalanceLock<T::Balance, T::BlockNumber>",
+            structure(&[
+                ("id", "LockIdentifier"),
+                ("amount", "Balance"),
+                ("until", "BlockNumber"),
+                ("reasons", "WithdrawReasons"),
+            ]),
+        ),
+        // before vesting was moved into its own module
+        (
+            "VestingSchedule<T::Balance, T::BlockNumber>",
+            structure(&[
+                ("locked", "Balance"),
+                ("per_block", "Balance"),
+                ("starting_block", "BlockNumber"),
+            ]),
+        ),
+    ];
+    types.extend(account_data());
+    types
+}
+
+fn authorship() -> ModuleTypes {
+    let mut types = vec![
+        (
+            "UncleEntryItem<T::BlockNumber, T::Hash, T::AccountId>",
+            enumeration(&[
+                ("InclusionHeight", "BlockNumber"),
+                ("Uncle", "(Hash, Option<AccountId>)"),
+            ]),
+        ),
+        ("T::Header", header()),
+        ("Header", header()),
+    ];
+    types.extend(digest());
+    types
+}
+
+/// the header of a block
+fn header() -> RustTypeMarker {
+    structure(&[
+        ("parent_hash", "Hash"),
+        ("number", "Compact<BlockNumber>"),
+        ("state_root", "Hash"),
+        ("extrinsics_root", "Hash"),
+        ("digest", "Vec<DigestItem>"),
+    ])
+}
+
+/// the exposure of a validator, which identifies it in offence reports
+fn exposure() -> ModuleTypes {
+    let individual = structure(&[("who", "AccountId"), ("value", "Compact<Balance>")]);
+    let exposure = structure(&[
+        ("total", "Compact<Balance>"),
+        ("own", "Compact<Balance>"),
+        ("others", "Vec<IndividualExposure>"),
+    ]);
+    let identification = ty("(AccountId, Exposure)");
+    vec![
+        ("IndividualExposure", individual),
+        ("Exposure", exposure.clone()),
+        ("Exposure<T::AccountId, BalanceOf<T>>", exposure),
+        ("IdentificationTuple", identification.clone()),
+        ("T::IdentificationTuple", identification),
+        ("T::ValidatorId", ty("AccountId")),
+        ("ValidatorId", ty("AccountId")),
+    ]
+}
+
+fn staking() -> ModuleTypes {
+    let mut types = vec![
+        ("EraIndex", RustTypeMarker::U32),
+        (
+            "StakingLedger<T::AccountId, BalanceOf<T>>",
+            structure(&[
+                ("stash", "AccountId"),
+                ("total", "Compact<Balance>"),
+                ("active", "Compact<Balance>"),
+                ("unlocking", "Vec<UnlockChunk>"),
+                ("last_reward", "Option<EraIndex>"),
+            ]),
+        ),
+        (
+            "UnlockChunk",
+            structure(&[("value", "Compact<Balance>"), ("era", "Compact<EraIndex>")]),
+        ),
+        (
+            "RewardDestination",
+            unit_enum(&["Staked", "Stash", "Controller"]),
+        ),
+        (
+            "ValidatorPrefs",
+            structure(&[("commission", "Compact<Perbill>")]),
+        ),
+        (
+            "Nominations<T::AccountId>",
+            structure(&[
+                ("targets", "Vec<AccountId>"),
+                ("submitted_in", "EraIndex"),
+                ("suppressed", "bool"),
+            ]),
+        ),
+        (
+            "ActiveEraInfo<MomentOf<T>>",
+            structure(&[("index", "EraIndex"), ("start", "Option<Moment>")]),
+        ),
+        (
+            "Forcing",
+            unit_enum(&["NotForcing", "ForceNew", "ForceNone", "ForceAlways"]),
+        ),
+        (
+            "UnappliedSlash<T::AccountId, BalanceOf<T>>",
+            structure(&[
+                ("validator", "AccountId"),
+                ("own", "Balance"),
+                ("others", "Vec<(AccountId, Balance)>"),
+                ("reporters", "Vec<AccountId>"),
+                ("payout", "Balance"),
+            ]),
+        ),
+        ("Points", RustTypeMarker::U32),
+        (
+            "EraPoints",
+            structure(&[("total", "Points"), ("individual", "Vec<Points>")]),
+        ),
+        ("slashing::SpanIndex", RustTypeMarker::U32),
+        (
+            "slashing::SlashingSpans",
+            structure(&[
+                ("span_index", "slashing::SpanIndex"),
+                ("last_start", "EraIndex"),
+                ("last_nonzero_slash", "EraIndex"),
+                ("prior", "Vec<EraIndex>"),
+            ]),
+        ),
+        (
+            "slashing::SpanRecord<BalanceOf<T>>",
+            structure(&[("slashed", "Balance"), ("paid_out", "Balance")]),
+        ),
+        ("Releases", unit_enum(&["V1_0_0", "V2_0_0"])),
+    ];
+    types.extend(exposure());
+    types
+}
+
+fn offences() -> ModuleTypes {
+    let mut types = vec![
+        ("Kind", ty("[u8; 16]")),
+        ("OpaqueTimeSlot", ty("Vec<u8>")),
+        ("ReportIdOf<T>", ty("Hash")),
+        (
+            "OffenceDetails<T::AccountId, T::IdentificationTuple>",
+            structure(&[
+                ("offender", "IdentificationTuple"),
+                ("reporters", "Vec<AccountId>"),
+            ]),
+        ),
+        (
+            "DeferredOffenceOf<T>",
+            RustTypeMarker::Tuple(vec![
+                ty("Vec<OffenceDetails<T::AccountId, T::IdentificationTuple>>"),
+                ty("Vec<Perbill>"),
+                ty("SessionIndex"),
+            ]),
+        ),
+    ];
+    types.extend(exposure());
+    types
+}
+
+/// the keys themselves differ between runtimes, see `session_keys`
+fn session() -> ModuleTypes {
+    vec![
+        ("T::ValidatorId", ty("AccountId")),
+        ("ValidatorId", ty("AccountId")),
+        ("KeyTypeId", ty("[u8; 4]")),
+    ]
+}
+
+fn grandpa() -> ModuleTypes {
+    vec![
+        ("AuthorityWeight", RustTypeMarker::U64),
+        ("AuthorityList", ty("Vec<(AuthorityId, AuthorityWeight)>")),
+        ("SetId", RustTypeMarker::U64),
+        ("fg_primitives::SetId", RustTypeMarker::U64),
+        (
+            "StoredPendingChange<T::BlockNumber>",
+            structure(&[
+                ("scheduled_at", "BlockNumber"),
+                ("delay", "BlockNumber"),
+                ("next_authorities", "AuthorityList"),
+                ("forced", "Option<BlockNumber>"),
+            ]),
+        ),
+        (
+            "StoredState<T::BlockNumber>",
+            enumeration(&[
+                ("Live", "()"),
+                ("PendingPause", "(BlockNumber, BlockNumber)"),
+                ("Paused", "()"),
+                ("PendingResume", "(BlockNumber, BlockNumber)"),
+            ]),
+        ),
+    ]
+}
+
+fn im_online() -> ModuleTypes {
+    let mut types = vec![
+        ("AuthIndex", RustTypeMarker::U32),
+        ("T::AuthorityId", ty("AuthorityId")),
+        (
+            "<T::AuthorityId as RuntimeAppPublic>::Signature",
+            ty("H512"),
+        ),
+        (
+            "OpaqueNetworkState",
+            structure(&[
+                ("peer_id", "Vec<u8>"),
+                ("external_addresses", "Vec<Vec<u8>>"),
+            ]),
+        ),
+        (
+            "Heartbeat<T::BlockNumber>",
+            structure(&[
+                ("block_number", "BlockNumber"),
+                ("network_state", "OpaqueNetworkState"),
+                ("session_index", "SessionIndex"),
+                ("authority_index", "AuthIndex"),
+            ]),
+        ),
+    ];
+    types.extend(exposure());
+    types
+}
+
+fn democracy() -> ModuleTypes {
+    vec![
+        ("PropIndex", RustTypeMarker::U32),
+        ("ReferendumIndex", RustTypeMarker::U32),
+        (
+            "VoteThreshold",
+            unit_enum(&[
+                "SuperMajorityApprove",
+                "SuperMajorityAgainst",
+                "SimpleMajority",
+            ]),
+        ),
+        (
+            "ReferendumInfo<T::BlockNumber, T::Hash>",
+            structure(&[
+                ("end", "BlockNumber"),
+                ("proposal_hash", "Hash"),
+                ("threshold", "VoteThreshold"),
+                ("delay", "BlockNumber"),
+            ]),
+        ),
+        // before proposals were stored as preimages
+        (
+            "ReferendumInfo<T::BlockNumber, T::Proposal>",
+            structure(&[
+                ("end", "BlockNumber"),
+                ("proposal", "T::Proposal"),
+                ("threshold", "VoteThreshold"),
+                ("delay", "BlockNumber"),
+            ]),
+        ),
+        // since referenda keep a tally of their votes
+        (
+            "ReferendumInfo<T::BlockNumber, T::Hash, BalanceOf<T>>",
+            enumeration(&[
+                ("Ongoing", "ReferendumStatus"),
+                ("Finished", "(bool, BlockNumber)"),
+            ]),
+        ),
+        (
+            "ReferendumStatus",
+            structure(&[
+                ("end", "BlockNumber"),
+                ("proposal_hash", "Hash"),
+                ("threshold", "VoteThreshold"),
+                ("delay", "BlockNumber"),
+                ("tally", "Tally"),
+            ]),
+        ),
+        (
+            "Tally",
+            structure(&[
+                ("ayes", "Balance"),
+                ("nays", "Balance"),
+                ("turnout", "Balance"),
+            ]),
+        ),
+        // the highest bit is set for an aye,
+        // the lower bits hold the conviction
+        ("Vote", RustTypeMarker::U8),
+        (
+            "Conviction",
+            unit_enum(&[
+                "None", "Locked1x", "Locked2x", "Locked3x", "Locked4x", "Locked5x",
+                "Locked6x",
+            ]),
+        ),
+    ]
+}
+
+fn collective() -> ModuleTypes {
+    vec![
+        ("ProposalIndex", RustTypeMarker::U32),
+        ("MemberCount", RustTypeMarker::U32),
+        (
+            "Votes<T::AccountId, T::BlockNumber>",
+            structure(&[
+                ("index", "ProposalIndex"),
+                ("threshold", "MemberCount"),
+                ("ayes", "Vec<AccountId>"),
+                ("nays", "Vec<AccountId>"),
+                ("end", "BlockNumber"),
+            ]),
+        ),
+        // before votes had an end
+        (
+            "Votes<T::AccountId>",
+            structure(&[
+                ("index", "ProposalIndex"),
+                ("threshold", "MemberCount"),
+                ("ayes", "Vec<AccountId>"),
+                ("nays", "Vec<AccountId>"),
+            ]),
+        ),
+    ]
+}
+
+fn membership() -> ModuleTypes {
+    // the `Dummy` event, which only exists to use the generic parameters
+    vec![
+        ("PhantomData<(AccountId, Event)>", RustTypeMarker::Null),
+        (
+            "rstd::marker::PhantomData<(AccountId, Event)>",
+            RustTypeMarker::Null,
+        ),
+        (
+            "sp_std::marker::PhantomData<(AccountId, Event)>",
+            RustTypeMarker::Null,
+        ),
+    ]
+}
+
+fn treasury() -> ModuleTypes {
+    vec![
+        ("ProposalIndex", RustTypeMarker::U32),
+        (
+            "Proposal<T::AccountId, BalanceOf<T>>",
+            structure(&[
+                ("proposer", "AccountId"),
+                ("value", "Balance"),
+                ("beneficiary", "AccountId"),
+                ("bond", "Balance"),
+            ]),
+        ),
+        (
+            "OpenTip<T::AccountId, BalanceOf<T>, T::BlockNumber, T::Hash>",
+            structure(&[
+                ("reason", "Hash"),
+                ("who", "AccountId"),
+                ("finder", "Option<(AccountId, Balance)>"),
+                ("closes", "Option<BlockNumber>"),
+                ("tips", "Vec<(AccountId, Balance)>"),
+            ]),
+        ),
+    ]
+}
+
+fn contracts() -> ModuleTypes {
+    let schedule = structure(&[
+        ("version", "u32"),
+        ("put_code_per_byte_cost", "Gas"),
+        ("grow_mem_cost", "Gas"),
+        ("regular_op_cost", "Gas"),
+        ("return_data_per_byte_cost", "Gas"),
+        ("event_data_per_byte_cost", "Gas"),
+        ("event_per_topic_cost", "Gas"),
+        ("event_base_cost", "Gas"),
+        ("call_base_cost", "Gas"),
+        ("instantiate_base_cost", "Gas"),
+        ("sandbox_data_read_cost", "Gas"),
+        ("sandbox_data_write_cost", "Gas"),
+        ("transfer_cost", "Gas"),
+        ("max_event_topics", "u32"),
+        ("max_stack_height", "u32"),
+        ("max_memory_pages", "u32"),
+        ("max_table_size", "u32"),
+        ("enable_println", "bool"),
+        ("max_subject_len", "u32"),
+    ]);
+    vec![
+        ("Gas", RustTypeMarker::U64),
+        ("CodeHash<T>", ty("Hash")),
+        ("TrieId", ty("Vec<u8>")),
+        ("Schedule", schedule),
+        (
+            "wasm::PrefabWasmModule",
+            structure(&[
+                ("schedule_version", "Compact<u32>"),
+                ("initial", "Compact<u32>"),
+                ("maximum", "Compact<u32>"),
+                ("_reserved", "Option<()>"),
+                ("code", "Vec<u8>"),
+            ]),
+        ),
+        (
+            "AliveContractInfo",
+            structure(&[
+                ("trie_id", "TrieId"),
+                ("storage_size", "u32"),
+                ("code_hash", "CodeHash<T>"),
+                ("rent_allowance", "Balance"),
+                ("deduct_block", "BlockNumber"),
+                ("last_write", "Option<BlockNumber>"),
+            ]),
+        ),
+        (
+            "ContractInfo<T>",
+            enumeration(&[("Alive", "AliveContractInfo"), ("Tombstone", "Hash")]),
+        ),
+    ]
+}
+
+fn vesting() -> ModuleTypes {
+    vec![(
+        "VestingInfo<BalanceOf<T>, T::BlockNumber>",
+        structure(&[
+            ("locked", "Balance"),
+            ("per_block", "Balance"),
+            ("starting_block", "BlockNumber"),
+        ]),
+    )]
+}
+
+fn identity() -> ModuleTypes {
+    // `Raw` data is stored inline, with its length encoded in the index of the
+    // variant
+    let mut data = vec![("None".to_string(), "()".to_string())];
+    data.extend((0..=32).map(|len| (format!("Raw{}", len), format!("[u8; {}]", len))));
+    for hash in &["BlakeTwo256", "Sha256", "Keccak256", "ShaThree256"] {
+        data.push((hash.to_string(), "H256".to_string()));
+    }
+    let data = data
+        .iter()
+        .map(|(n, t)| (n.as_str(), t.as_str()))
+        .collect::<Vec<_>>();
+
+    let judgement = enumeration(&[
+        ("Unknown", "()"),
+        ("FeePaid", "Balance"),
+        ("Reasonable", "()"),
+        ("KnownGood", "()"),
+        ("OutOfDate", "()"),
+        ("LowQuality", "()"),
+        ("Erroneous", "()"),
+    ]);
+    let info = structure(&[
+        ("additional", "Vec<(Data, Data)>"),
+        ("display", "Data"),
+        ("legal", "Data"),
+        ("web", "Data"),
+        ("riot", "Data"),
+        ("email", "Data"),
+        ("pgp_fingerprint", "Option<H160>"),
+        ("image", "Data"),
+    ]);
+    vec![
+        ("Data", enumeration(&data)),
+        ("RegistrarIndex", RustTypeMarker::U32),
+        ("IdentityFields", RustTypeMarker::U64),
+        ("Judgement<BalanceOf<T>>", judgement),
+        ("IdentityInfo", info),
+        (
+            "Registration<BalanceOf<T>>",
+            structure(&[
+                (
+                    "judgements",
+                    "Vec<(RegistrarIndex, Judgement<BalanceOf<T>>)>",
+                ),
+                ("deposit", "Balance"),
+                ("info", "IdentityInfo"),
+            ]),
+        ),
+        (
+            "RegistrarInfo<BalanceOf<T>, T::AccountId>",
+            structure(&[
+                ("account", "AccountId"),
+                ("fee", "Balance"),
+                ("fields", "IdentityFields"),
+            ]),
+        ),
+    ]
+}
+
+fn society() -> ModuleTypes {
+    let bid_kind =
+        enumeration(&[("Deposit", "Balance"), ("Vouch", "(AccountId, Balance)")]);
+    let bid = structure(&[
+        ("who", "AccountId"),
+        ("kind", "BidKind<T::AccountId, BalanceOf<T, I>>"),
+        ("value", "Balance"),
+    ]);
+    vec![
+        ("BidKind<T::AccountId, BalanceOf<T, I>>", bid_kind),
+        ("Bid<T::AccountId, BalanceOf<T, I>>", bid),
+        ("StrikeCount", RustTypeMarker::U32),
+        ("VouchingStatus", unit_enum(&["Vouching", "Banned"])),
+        ("Vote", unit_enum(&["Skeptic", "Reject", "Approve"])),
+        ("Judgement", unit_enum(&["Rebid", "Reject", "Approve"])),
+    ]
+}
+
+fn recovery() -> ModuleTypes {
+    vec![
+        (
+            "RecoveryConfig<T::BlockNumber, BalanceOf<T>, T::AccountId>",
+            structure(&[
+                ("delay_period", "BlockNumber"),
+                ("deposit", "Balance"),
+                ("friends", "Vec<AccountId>"),
+                ("threshold", "u16"),
+            ]),
+        ),
+        (
+            "ActiveRecovery<T::BlockNumber, BalanceOf<T>, T::AccountId>",
+            structure(&[
+                ("created", "BlockNumber"),
+                ("deposit", "Balance"),
+                ("friends", "Vec<AccountId>"),
+            ]),
+        ),
+    ]
+}
+
+fn utility() -> ModuleTypes {
+    let timepoint = structure(&[("height", "BlockNumber"), ("index", "u32")]);
+    vec![
+        ("Timepoint<T::BlockNumber>", timepoint.clone()),
+        ("Timepoint<BlockNumber>", timepoint),
+        ("CallHash", ty("[u8; 32]")),
+        (
+            "Multisig<T::BlockNumber, BalanceOf<T>, T::AccountId>",
+            structure(&[
+                ("when", "Timepoint<T::BlockNumber>"),
+                ("deposit", "Balance"),
+                ("depositor", "AccountId"),
+                ("approvals", "Vec<AccountId>"),
+            ]),
+        ),
+    ]
+}
@@ -0,0 +1,202 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Type definitions of the Kusama runtime, since genesis
+//!
+//! Kusama runs the modules of Polkadot, and has been upgraded many times.
+//! Types are registered for ranges of spec versions: the types of FRAME and
+//! the relay chain for every version, and the definitions which changed with
+//! an upgrade for the versions they were in use. The metadata of every
+//! version may be registered before or after the types
+
+use crate::{
+    definitions::{register_module_range, structure, ty, ModuleTypes},
+    frame, parachains,
+};
+use core::decoder::{Decoder, RustTypeMarker, SpecRange};
+
+/// every module which has been part of a Kusama runtime
+const MODULES: &[&str] = &[
+    "System",
+    "RandomnessCollectiveFlip",
+    "Babe",
+    "Timestamp",
+    "Indices",
+    "Balances",
+    "TransactionPayment",
+    "Authorship",
+    "Staking",
+    "Offences",
+    "Session",
+    "FinalityTracker",
+    "Grandpa",
+    "ImOnline",
+    "AuthorityDiscovery",
+    "Democracy",
+    "Council",
+    "TechnicalCommittee",
+    "ElectionsPhragmen",
+    "TechnicalMembership",
+    "Treasury",
+    "Claims",
+    "Parachains",
+    "Attestations",
+    "Slots",
+    "Registrar",
+    "Sudo",
+    "Nicks",
+    "Identity",
+    "Utility",
+    "Society",
+    "Recovery",
+    "Vesting",
+];
+
+/// Register the types of every Kusama runtime
+pub fn register(decoder: &mut Decoder) {
+    frame::register_types_range(decoder, SpecRange::all(), MODULES, module_types);
+    for &module in MODULES {
+        for (specs, types) in primitives_history().into_iter().chain(history(module)) {
+            register_module_range(decoder, specs, module, &types);
+        }
+    }
+    // `AnySignature`, before signatures named their scheme
+    decoder.register_runtime_marker_range(..= 1018, "Signature", ty("H512"));
+}
+
+/// types of Kusama specific to `module`
+fn module_types(module: &str) -> Option<ModuleTypes> {
+    match module {
+        "Session" => Some(frame::session_keys(&[
+            "grandpa",
+            "babe",
+            "im_online",
+            "parachain_validator",
+            "authority_discovery",
+        ])),
+        _ => parachains::module_types(module),
+    }
+}
+
+/// definitions of the types every module is configured with, which changed
+/// with an upgrade
+fn primitives_history() -> Vec<(SpecRange, ModuleTypes)> {
+    // errors of a module, before `DispatchError` became an enum
+    let dispatch_error = structure(&[("module", "Option<u8>"), ("error", "u8")]);
+    let dispatch_result = RustTypeMarker::Result(
+        Box::new(RustTypeMarker::Null),
+        Box::new(dispatch_error.clone()),
+    );
+    vec![
+        (
+            SpecRange::new(..= 1031),
+            vec![
+                ("DispatchError", dispatch_error),
+                ("DispatchResult", dispatch_result),
+            ],
+        ),
+        (SpecRange::new(..= 1056), vec![("Weight", RustTypeMarker::U32)]),
+        (SpecRange::new(1057 ..), vec![("Weight", RustTypeMarker::U64)]),
+    ]
+}
+
+/// definitions of types specific to `module`, which changed with an upgrade
+fn history(module: &str) -> Vec<(SpecRange, ModuleTypes)> {
+    match module {
+        "System" => vec![(
+            SpecRange::new(..= 1031),
+            vec![(
+                "DispatchInfo",
+                structure(&[("weight", "Weight"), ("class", "DispatchClass")]),
+            )],
+        )],
+        "Staking" => staking_history(),
+        _ => Vec::new(),
+    }
+}
+
+fn staking_history() -> Vec<(SpecRange, ModuleTypes)> {
+    let ledger = "StakingLedger<T::AccountId, BalanceOf<T>>";
+    vec![
+        // before the last era a stash was rewarded for was tracked
+        (
+            SpecRange::new(..= 1042),
+            vec![
+                (
+                    ledger,
+                    structure(&[
+                        ("stash", "AccountId"),
+                        ("total", "Compact<Balance>"),
+                        ("active", "Compact<Balance>"),
+                        ("unlocking", "Vec<UnlockChunk>"),
+                    ]),
+                ),
+                (
+                    "slashing::SlashingSpans",
+                    structure(&[
+                        ("span_index", "slashing::SpanIndex"),
+                        ("last_start", "EraIndex"),
+                        ("prior", "Vec<EraIndex>"),
+                    ]),
+                ),
+            ],
+        ),
+        // since rewards are paid out lazily
+        (
+            SpecRange::new(1058 ..),
+            vec![(
+                ledger,
+                structure(&[
+                    ("stash", "AccountId"),
+                    ("total", "Compact<Balance>"),
+                    ("active", "Compact<Balance>"),
+                    ("unlocking", "Vec<UnlockChunk>"),
+                    ("claimed_rewards", "Vec<EraIndex>"),
+                ]),
+            )],
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{metadata::Metadata, RuntimeVersion};
+
+    fn runtime_version(spec_version: u32) -> RuntimeVersion {
+        RuntimeVersion {
+            spec_version,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_resolve_every_type() {
+        let mut decoder = Decoder::new();
+        let genesis = include_bytes!("../../../core/test/metadata_v9_block6.bin");
+        let genesis = Metadata::from_bytes(genesis).unwrap();
+        decoder.register_version(genesis, runtime_version(1020));
+        register(&mut decoder);
+        // registered after the types
+        let cc3 = include_bytes!("../../../core/test/metadata_v9.bin");
+        let cc3 = Metadata::from_bytes(cc3).unwrap();
+        decoder.register_version(cc3, runtime_version(1031));
+
+        if let Err(unresolved) = decoder.verify() {
+            panic!("unresolved types: {:#?}", unresolved);
+        }
+    }
+}
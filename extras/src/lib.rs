@@ -1,5 +1,14 @@
 extern crate alloc;
 
+#[cfg(any(feature = "polkadot", feature = "kusama", feature = "substrate_dev"))]
+mod definitions;
+#[cfg(any(feature = "polkadot", feature = "kusama", feature = "substrate_dev"))]
+mod frame;
+#[cfg(any(feature = "polkadot", feature = "kusama"))]
+mod parachains;
+
+#[cfg(feature = "kusama")]
+pub mod kusama;
 #[cfg(feature = "polkadot")]
 pub mod polkadot;
 
@@ -0,0 +1,194 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Type definitions of the relay chain modules
+//!
+//! Polkadot and Kusama share the modules handling parachains, their
+//! auctions and the claims of tokens bought on Ethereum
+
+use crate::definitions::{enumeration, structure, ty, unit_enum, ModuleTypes};
+use core::decoder::RustTypeMarker;
+
+/// types specific to a relay chain module
+pub fn module_types(module: &str) -> Option<ModuleTypes> {
+    let types = match module {
+        "Parachains" => parachains(),
+        "Attestations" => attestations(),
+        "Slots" => slots(),
+        "Registrar" => registrar(),
+        "Claims" => claims(),
+        _ => return None,
+    };
+    Some(types)
+}
+
+/// types of the parachain candidates which are included in the relay chain
+fn candidates() -> ModuleTypes {
+    vec![
+        ("ParaId", RustTypeMarker::U32),
+        ("CollatorId", ty("[u8; 32]")),
+        ("HeadData", ty("Vec<u8>")),
+        ("ValidatorSignature", ty("H512")),
+        (
+            "ParachainDispatchOrigin",
+            unit_enum(&["Signed", "Parachain", "Root"]),
+        ),
+        (
+            "UpwardMessage",
+            structure(&[("origin", "ParachainDispatchOrigin"), ("data", "Vec<u8>")]),
+        ),
+        (
+            "CandidateReceipt",
+            structure(&[
+                ("parachain_index", "ParaId"),
+                ("collator", "CollatorId"),
+                ("signature", "ValidatorSignature"),
+                ("head_data", "HeadData"),
+                ("egress_queue_roots", "Vec<(ParaId, Hash)>"),
+                ("fees", "Balance"),
+                ("block_data_hash", "Hash"),
+                ("upward_messages", "Vec<UpwardMessage>"),
+                ("erasure_root", "Hash"),
+            ]),
+        ),
+        (
+            "ValidityAttestation",
+            enumeration(&[
+                ("Never", "()"),
+                ("Implicit", "ValidatorSignature"),
+                ("Explicit", "ValidatorSignature"),
+            ]),
+        ),
+        (
+            "AttestedCandidate",
+            structure(&[
+                ("candidate", "CandidateReceipt"),
+                ("validity_votes", "Vec<ValidityAttestation>"),
+                ("validator_indices", "BitVec"),
+            ]),
+        ),
+    ]
+}
+
+fn parachains() -> ModuleTypes {
+    let mut types = vec![
+        ("ValidatorId", ty("[u8; 32]")),
+        (
+            "Retriable",
+            enumeration(&[("Never", "()"), ("WithRetries", "u32")]),
+        ),
+    ];
+    types.extend(candidates());
+    types
+}
+
+fn attestations() -> ModuleTypes {
+    let mut types = vec![
+        ("MoreAttestations", RustTypeMarker::Null),
+        (
+            "IncludedBlocks<T>",
+            structure(&[
+                ("actual_number", "BlockNumber"),
+                ("session", "SessionIndex"),
+                ("random_seed", "H256"),
+                ("active_parachains", "Vec<ParaId>"),
+                ("para_blocks", "Vec<Hash>"),
+            ]),
+        ),
+        (
+            "BlockAttestations<T>",
+            structure(&[
+                ("receipt", "CandidateReceipt"),
+                ("valid", "Vec<AccountId>"),
+                ("invalid", "Vec<AccountId>"),
+            ]),
+        ),
+    ];
+    types.extend(candidates());
+    types
+}
+
+fn slots() -> ModuleTypes {
+    let new_bidder = structure(&[("who", "AccountId"), ("sub", "SubId")]);
+    let range = unit_enum(&[
+        "ZeroZero",
+        "ZeroOne",
+        "ZeroTwo",
+        "ZeroThree",
+        "OneOne",
+        "OneTwo",
+        "OneThree",
+        "TwoTwo",
+        "TwoThree",
+        "ThreeThree",
+    ]);
+    vec![
+        ("ParaId", RustTypeMarker::U32),
+        ("ParaIdOf<T>", RustTypeMarker::U32),
+        ("SubId", RustTypeMarker::U32),
+        ("AuctionIndex", RustTypeMarker::U32),
+        ("LeasePeriod", RustTypeMarker::U32),
+        ("LeasePeriodOf<T>", RustTypeMarker::U32),
+        ("SlotRange", range),
+        ("NewBidder<AccountId>", new_bidder.clone()),
+        ("NewBidder<T::AccountId>", new_bidder),
+        (
+            "Bidder",
+            enumeration(&[("New", "NewBidder<AccountId>"), ("Existing", "ParaId")]),
+        ),
+        ("WinningData<T>", ty("[Option<(Bidder, Balance)>; 10]")),
+        (
+            "IncomingParachain<T::AccountId, T::Hash>",
+            enumeration(&[
+                ("Unset", "NewBidder<AccountId>"),
+                ("Fixed", "(Hash, Vec<u8>)"),
+                ("Deploy", "(Vec<u8>, Vec<u8>)"),
+            ]),
+        ),
+    ]
+}
+
+fn registrar() -> ModuleTypes {
+    let mut types = vec![
+        ("Scheduling", unit_enum(&["Always", "Dynamic"])),
+        ("ParaInfo", structure(&[("scheduling", "Scheduling")])),
+        ("ParathreadClaim", ty("(ParaId, CollatorId)")),
+        (
+            "QueuedParathread",
+            structure(&[("claim", "ParathreadClaim"), ("core_offset", "u32")]),
+        ),
+        (
+            "ParathreadClaimQueue",
+            structure(&[
+                ("queue", "Vec<QueuedParathread>"),
+                ("next_core_offset", "u32"),
+            ]),
+        ),
+        (
+            "Retriable",
+            enumeration(&[("Never", "()"), ("WithRetries", "u32")]),
+        ),
+    ];
+    types.extend(candidates());
+    types
+}
+
+fn claims() -> ModuleTypes {
+    vec![
+        ("EthereumAddress", ty("H160")),
+        ("EcdsaSignature", ty("[u8; 65]")),
+    ]
+}
@@ -14,10 +14,103 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::decoder::Decoder;
-use polkadot_runtime::{Runtime as RuntimeLatest, RuntimeVersion as RuntimeVersionLatest};
+//! Type definitions of the Polkadot runtime
+//!
+//! Polkadot is built with FRAME, and adds its own modules for parachains
+//! and the claims of DOT on top
 
+use crate::{definitions::ModuleTypes, frame, parachains};
+use codec::Encode;
+use core::{decoder::Decoder, metadata::Metadata as RawMetadata, Error, RuntimeVersion};
+use polkadot_runtime::{Runtime as RuntimeLatest, VERSION as VERSION_LATEST};
+
+/// Create a decoder for the latest Polkadot runtime
 pub fn register() -> Decoder {
     let mut decoder = Decoder::new();
-   
+    let meta = RawMetadata::new(&RuntimeLatest::metadata().encode());
+    let modules = meta
+        .modules()
+        .map(|m| m.name().to_string())
+        .collect::<Vec<_>>();
+    let version = runtime_version();
+    decoder.register_version(meta, version.clone());
+    register_types(&mut decoder, &version, &modules)
+        .expect("every module is part of the metadata");
+    decoder
+}
+
+/// Register the types of Polkadot for the runtime at `version`
+///
+/// `modules` are the names of the modules in the metadata of that runtime
+pub fn register_types(
+    decoder: &mut Decoder, version: &RuntimeVersion, modules: &[String],
+) -> Result<(), Error> {
+    frame::register_types(decoder, version, modules, module_types)
+}
+
+/// the version of the runtime, as known to desub
+fn runtime_version() -> RuntimeVersion {
+    RuntimeVersion {
+        spec_name: VERSION_LATEST.spec_name.to_string(),
+        impl_name: VERSION_LATEST.impl_name.to_string(),
+        authoring_version: VERSION_LATEST.authoring_version,
+        spec_version: VERSION_LATEST.spec_version,
+        impl_version: VERSION_LATEST.impl_version,
+        apis: VERSION_LATEST.apis.to_vec(),
+    }
+}
+
+/// types of Polkadot specific to `module`
+fn module_types(module: &str) -> Option<ModuleTypes> {
+    match module {
+        "Session" => Some(frame::session_keys(&[
+            "grandpa",
+            "babe",
+            "im_online",
+            "parachain_validator",
+            "authority_discovery",
+        ])),
+        _ => parachains::module_types(module),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_resolve_every_type() {
+        let decoder = register();
+        if let Err(unresolved) = decoder.verify() {
+            panic!("unresolved types: {:#?}", unresolved);
+        }
+    }
+
+    #[test]
+    fn should_register_polkadot() {
+        let decoder = register();
+        // unsigned `System::remark(b"desub")`
+        let mut ext = vec![4, 0, 1];
+        ext.extend(b"desub".to_vec().encode());
+        let ext = decoder
+            .decode_extrinsic(VERSION_LATEST.spec_version, &ext.encode())
+            .unwrap();
+        assert_eq!(ext.call.module, "System");
+        assert_eq!(ext.call.call, "remark");
+    }
+
+    #[test]
+    fn should_decode_identity_data() {
+        let decoder = register();
+        // `Raw3(b"dot")`
+        let data = vec![4, b'd', b'o', b't'];
+        decoder
+            .decode(
+                VERSION_LATEST.spec_version,
+                "Identity".into(),
+                "Data".into(),
+                data,
+            )
+            .unwrap();
+    }
 }
@@ -14,119 +14,63 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::{decoder::Decoder, metadata::Metadata as RawMetadata};
-use node_runtime::{Runtime as RuntimeLatest, VERSION as VERSION_LATEST};
-use node_primitives::{AccountId, AccountIndex, Balance, BlockNumber, Hash, Index, Moment, Signature};
+//! Type definitions of the substrate development node (`node-runtime`)
+//!
+//! Every module of `node-runtime` is part of FRAME, so the runtime only
+//! adds its session keys
+
+use crate::{definitions::ModuleTypes, frame};
 use codec::Encode;
-use type_metadata::{Metadata, tuple_meta_type};
+use core::{decoder::Decoder, metadata::Metadata as RawMetadata, Error, RuntimeVersion};
+use node_runtime::{Runtime as RuntimeLatest, VERSION as VERSION_LATEST};
 
+/// Create a decoder for the latest `node-runtime`
 pub fn register() -> Decoder {
-    env_logger::init();
     let mut decoder = Decoder::new();
     let meta = RawMetadata::new(&RuntimeLatest::metadata().encode());
-    println!("{}", meta.detailed_pretty());
-    decoder.register_version(meta, VERSION_LATEST);
-
-//     system(&mut decoder);
-//     println!("\n{:?}", decoder);
+    let modules = meta
+        .modules()
+        .map(|m| m.name().to_string())
+        .collect::<Vec<_>>();
+    let version = runtime_version();
+    decoder.register_version(meta, version.clone());
+    register_types(&mut decoder, &version, &modules)
+        .expect("every module is part of the metadata");
     decoder
 }
 
-fn system(decoder: &mut Decoder) {
-/*
-    decoder.register::<TempOrigin,_>(
-        &VERSION_LATEST,
-        "System",
-        "T::Origin"
-    );
-*/
-    /*decoder.register::<node_runtime::Call, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Call"
-);
-
-    decoder.register::<TempIndex, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Index"
-    );*/
-    decoder.register::<node_primitives::BlockNumber, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::BlockNumber"
-    );
+/// Register the types of `node-runtime` for the runtime at `version`
+///
+/// `modules` are the names of the modules in the metadata of that runtime
+pub fn register_types(
+    decoder: &mut Decoder, version: &RuntimeVersion, modules: &[String],
+) -> Result<(), Error> {
+    frame::register_types(decoder, version, modules, module_types)
+}
 
-    /*
-    #[derive(Metadata)]
-    struct TempHash(node_primitives::Hash);
-    decoder.register::<TempHash, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Hash"
-    );
-     */
-    /* decoder.register::<node_runtime::Hashing, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Hashing",
-);*/
-    /*
-    decoder.register::<node_primitives::AccountId, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::AccountId"
-    );
-    */
-    /*decoder.register::<node_runtime::Lookup, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Lookup"
-);
-    decoder.register::<node_primitives::Header, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Header"
-    );
-    */
-    /*decoder.register::<node_runtime::Event, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Event"
-);*/
-    /*
-    decoder.register::<node_runtime::BlockHashCount, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::BlockHashCount"
-);
+/// the version of the runtime, as known to desub
+fn runtime_version() -> RuntimeVersion {
+    RuntimeVersion {
+        spec_name: VERSION_LATEST.spec_name.to_string(),
+        impl_name: VERSION_LATEST.impl_name.to_string(),
+        authoring_version: VERSION_LATEST.authoring_version,
+        spec_version: VERSION_LATEST.spec_version,
+        impl_version: VERSION_LATEST.impl_version,
+        apis: VERSION_LATEST.apis.to_vec(),
+    }
+}
 
-    decoder.register::<node_runtime::MaximumBlockWeight, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::MaximumBlockWeight"
-    );
-    decoder.register::<node_runtime::MaximumBlockLength, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::MaxiumumBlockLength"
-    );
-    decoder.register::<node_runtime::AvailableBlockRatio, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::AvailableBlockRatio"
-    );
-    decoder.register::<node_runtime::Version, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::Version"
-    );
-    decoder.register::<node_runtime::ModuleToIndex, _>(
-        &VERSION_LATEST,
-        "System",
-        "T::ModuleToIndex"
-    );
-    */
+/// types of `node-runtime` specific to `module`
+fn module_types(module: &str) -> Option<ModuleTypes> {
+    match module {
+        "Session" => Some(frame::session_keys(&[
+            "grandpa",
+            "babe",
+            "im_online",
+            "authority_discovery",
+        ])),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -134,8 +78,31 @@ mod tests {
     use super::*;
 
     #[test]
-    fn should_get_metadata() {
+    fn should_resolve_every_type() {
+        let decoder = register();
+        if let Err(unresolved) = decoder.verify() {
+            panic!("unresolved types: {:#?}", unresolved);
+        }
+    }
+
+    #[test]
+    fn should_decode_transfer() {
         let decoder = register();
-        log::info!("{:#?}", decoder);
+        let balances = RawMetadata::new(&RuntimeLatest::metadata().encode())
+            .module("Balances")
+            .unwrap();
+        // unsigned transfer of 1000 units to an `AccountId` address
+        let mut ext = vec![4];
+        ext.extend(
+            balances
+                .call("transfer", (0xffu8, [1u8; 32], codec::Compact(1_000u128)))
+                .unwrap()
+                .0,
+        );
+        let ext = decoder
+            .decode_extrinsic(VERSION_LATEST.spec_version, &ext.encode())
+            .unwrap();
+        assert_eq!(ext.call.module, "Balances");
+        assert_eq!(ext.call.call, "transfer");
     }
 }
@@ -0,0 +1,97 @@
+#ifndef DESUB_H
+#define DESUB_H
+
+/* Generated with cbindgen from desub-ffi, do not edit: cbindgen --config cbindgen.toml --output include/desub.h */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// An opaque handle to a decoder
+typedef struct DesubDecoder DesubDecoder;
+
+// Decode the events stored under the `System Events` storage key to JSON
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed,
+// `data` must point to `len` readable bytes
+char *desub_decode_events(const DesubDecoder *decoder,
+                          uint32_t spec_version,
+                          const uint8_t *data,
+                          uintptr_t len);
+
+// Decode an extrinsic, including its length prefix, to JSON
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed,
+// `data` must point to `len` readable bytes
+char *desub_decode_extrinsic(const DesubDecoder *decoder,
+                             uint32_t spec_version,
+                             const uint8_t *data,
+                             uintptr_t len);
+
+// Decode the value of the storage entry `storage` of `module` to JSON
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed,
+// `module` and `storage` must be valid nul-terminated strings,
+// `data` must point to `len` readable bytes
+char *desub_decode_storage(const DesubDecoder *decoder,
+                           uint32_t spec_version,
+                           const char *module,
+                           const char *storage,
+                           const uint8_t *data,
+                           uintptr_t len);
+
+// Release a decoder
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed
+void desub_decoder_free(DesubDecoder *decoder);
+
+// Load a decoder serialized with `desub_decoder_to_json`
+//
+// # Safety
+//
+// `json` must be a valid nul-terminated string
+DesubDecoder *desub_decoder_from_json(const char *json);
+
+// Create a decoder without any registered runtime version
+DesubDecoder *desub_decoder_new(void);
+
+// Register the SCALE-encoded metadata of a runtime version
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed,
+// `metadata` must point to `len` readable bytes
+bool desub_decoder_register_metadata(DesubDecoder *decoder,
+                                     uint32_t spec_version,
+                                     const uint8_t *metadata,
+                                     uintptr_t len);
+
+// Serialize a decoder to JSON
+//
+// # Safety
+//
+// `decoder` must have been created by this library and not yet freed
+char *desub_decoder_to_json(const DesubDecoder *decoder);
+
+// The message of the last error on this thread, or `NULL` if there was none
+//
+// The string must be released with `desub_string_free`
+char *desub_last_error(void);
+
+// Release a string returned by this library
+//
+// # Safety
+//
+// `s` must have been returned by this library and not yet freed
+void desub_string_free(char *s);
+
+#endif /* DESUB_H */
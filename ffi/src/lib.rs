@@ -0,0 +1,357 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! C API of the decoder
+//!
+//! The header `include/desub.h` is generated with
+//! `cbindgen --config cbindgen.toml --output include/desub.h`.
+//!
+//! Decoded values are returned as JSON strings owned by the caller, which
+//! must be released with `desub_string_free`. Functions returning a pointer
+//! return `NULL` on failure and functions returning a `bool` return
+//! `false`; the reason is then available from `desub_last_error`.
+//!
+//! A `DesubDecoder` must only be used from the thread that created it
+
+use desub_core::{decoder::Decoder, metadata::Metadata, RuntimeVersion};
+use std::{
+    cell::RefCell,
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+/// An opaque handle to a decoder
+pub struct DesubDecoder(Decoder);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = RefCell::new(None);
+}
+
+fn set_last_error<E: ToString>(err: E) {
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(err.to_string()));
+}
+
+/// The message of the last error on this thread, or `NULL` if there was none
+///
+/// The string must be released with `desub_string_free`
+#[no_mangle]
+pub extern "C" fn desub_last_error() -> *mut c_char {
+    guard(|| {
+        Ok(LAST_ERROR.with(|e| match e.borrow_mut().take() {
+            Some(err) => into_c_string(err),
+            None => ptr::null_mut(),
+        }))
+    })
+}
+
+/// Create a decoder without any registered runtime version
+#[no_mangle]
+pub extern "C" fn desub_decoder_new() -> *mut DesubDecoder {
+    guard(|| Ok(Box::into_raw(Box::new(DesubDecoder(Decoder::new())))))
+}
+
+/// Load a decoder serialized with `desub_decoder_to_json`
+///
+/// # Safety
+///
+/// `json` must be a valid nul-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn desub_decoder_from_json(
+    json: *const c_char,
+) -> *mut DesubDecoder {
+    guard(|| {
+        let decoder = Decoder::from_json(to_str(json)?).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(DesubDecoder(decoder))))
+    })
+}
+
+/// Serialize a decoder to JSON
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn desub_decoder_to_json(
+    decoder: *const DesubDecoder,
+) -> *mut c_char {
+    guard(|| Ok(into_c_string(to_decoder(decoder)?.to_json())))
+}
+
+/// Register the SCALE-encoded metadata of a runtime version
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed,
+/// `metadata` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn desub_decoder_register_metadata(
+    decoder: *mut DesubDecoder, spec_version: u32, metadata: *const u8, len: usize,
+) -> bool {
+    guard(|| {
+        let decoder = to_decoder_mut(decoder)?;
+        let metadata =
+            Metadata::from_bytes(to_bytes(metadata, len)?).map_err(|e| e.to_string())?;
+        let version = RuntimeVersion {
+            spec_version,
+            ..Default::default()
+        };
+        decoder.register_version(metadata, version);
+        Ok(true)
+    })
+}
+
+/// Decode an extrinsic, including its length prefix, to JSON
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed,
+/// `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn desub_decode_extrinsic(
+    decoder: *const DesubDecoder, spec_version: u32, data: *const u8, len: usize,
+) -> *mut c_char {
+    guard(|| {
+        let decoder = to_decoder(decoder)?;
+        to_json(decoder.decode_extrinsic(spec_version, to_bytes(data, len)?))
+    })
+}
+
+/// Decode the events stored under the `System Events` storage key to JSON
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed,
+/// `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn desub_decode_events(
+    decoder: *const DesubDecoder, spec_version: u32, data: *const u8, len: usize,
+) -> *mut c_char {
+    guard(|| {
+        let decoder = to_decoder(decoder)?;
+        to_json(decoder.decode_events(spec_version, to_bytes(data, len)?))
+    })
+}
+
+/// Decode the value of the storage entry `storage` of `module` to JSON
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed,
+/// `module` and `storage` must be valid nul-terminated strings,
+/// `data` must point to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn desub_decode_storage(
+    decoder: *const DesubDecoder, spec_version: u32, module: *const c_char,
+    storage: *const c_char, data: *const u8, len: usize,
+) -> *mut c_char {
+    guard(|| {
+        let decoder = to_decoder(decoder)?;
+        let (module, storage) = (to_str(module)?, to_str(storage)?);
+        let data = to_bytes(data, len)?;
+        to_json(decoder.decode_storage(spec_version, module, storage, data))
+    })
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `s` must have been returned by this library and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn desub_string_free(s: *mut c_char) {
+    guard(|| {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+        Ok(())
+    })
+}
+
+/// Release a decoder
+///
+/// # Safety
+///
+/// `decoder` must have been created by this library and not yet freed
+#[no_mangle]
+pub unsafe extern "C" fn desub_decoder_free(decoder: *mut DesubDecoder) {
+    guard(|| {
+        if !decoder.is_null() {
+            drop(Box::from_raw(decoder));
+        }
+        Ok(())
+    })
+}
+
+/// The value an exported function returns on failure
+trait Failure {
+    fn failure() -> Self;
+}
+
+impl<T> Failure for *mut T {
+    fn failure() -> Self {
+        ptr::null_mut()
+    }
+}
+
+impl Failure for bool {
+    fn failure() -> Self {
+        false
+    }
+}
+
+impl Failure for () {
+    fn failure() -> Self {}
+}
+
+/// Run the body of an exported function
+///
+/// Errors and panics, which must not unwind into C, return the failure value
+/// of the function and become the last error
+fn guard<T, F>(body: F) -> T
+where
+    T: Failure,
+    F: FnOnce() -> Result<T, String>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(e)) => {
+            set_last_error(e);
+            T::failure()
+        }
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("panicked: {}", message));
+            T::failure()
+        }
+    }
+}
+
+unsafe fn to_decoder<'a>(decoder: *const DesubDecoder) -> Result<&'a Decoder, String> {
+    decoder
+        .as_ref()
+        .map(|d| &d.0)
+        .ok_or_else(|| "unexpected NULL decoder".to_string())
+}
+
+unsafe fn to_decoder_mut<'a>(
+    decoder: *mut DesubDecoder,
+) -> Result<&'a mut Decoder, String> {
+    decoder
+        .as_mut()
+        .map(|d| &mut d.0)
+        .ok_or_else(|| "unexpected NULL decoder".to_string())
+}
+
+unsafe fn to_str<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("unexpected NULL string".to_string());
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| e.to_string())
+}
+
+/// `NULL` is rejected even if `len` is 0, as slices must never be NULL
+unsafe fn to_bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], String> {
+    if data.is_null() {
+        return Err("unexpected NULL data".to_string());
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+fn to_json<T: serde::Serialize>(
+    value: Result<T, desub_core::Error>,
+) -> Result<*mut c_char, String> {
+    let value = value.map_err(|e| e.to_string())?;
+    let json = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+    Ok(into_c_string(json))
+}
+
+/// nul bytes, which may only be part of error messages, are replaced
+fn into_c_string(s: String) -> *mut c_char {
+    let s = if s.contains('\0') {
+        s.replace('\0', "\u{fffd}")
+    } else {
+        s
+    };
+    CString::new(s).expect("nul bytes were replaced; qed").into_raw()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &[u8] = include_bytes!("../../core/test/metadata_v9.bin");
+
+    unsafe fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let owned = CStr::from_ptr(s).to_str().unwrap().to_string();
+        desub_string_free(s);
+        owned
+    }
+
+    #[test]
+    fn should_decode_through_c_api() {
+        unsafe {
+            let decoder = desub_decoder_new();
+            assert!(desub_decoder_register_metadata(
+                decoder,
+                1031,
+                METADATA.as_ptr(),
+                METADATA.len()
+            ));
+            let json = CString::new(take_string(desub_decoder_to_json(decoder))).unwrap();
+            desub_decoder_free(decoder);
+
+            let decoder = desub_decoder_from_json(json.as_ptr());
+            let events = [0u8];
+            let decoded =
+                desub_decode_events(decoder, 1031, events.as_ptr(), events.len());
+            assert_eq!(take_string(decoded), "[]");
+
+            let decoded = desub_decode_events(decoder, 1, events.as_ptr(), events.len());
+            assert!(decoded.is_null());
+            assert!(take_string(desub_last_error()).contains("Spec Version 1"));
+            assert!(desub_last_error().is_null());
+            desub_decoder_free(decoder);
+        }
+    }
+
+    #[test]
+    fn should_reject_null_pointers() {
+        unsafe {
+            let decoder = desub_decoder_new();
+            assert!(!desub_decoder_register_metadata(decoder, 1031, ptr::null(), 0));
+            assert!(take_string(desub_last_error()).contains("NULL data"));
+            let decoded = desub_decode_events(ptr::null(), 1031, ptr::null(), 0);
+            assert!(decoded.is_null());
+            assert!(take_string(desub_last_error()).contains("NULL decoder"));
+            desub_decoder_free(decoder);
+        }
+    }
+
+    #[test]
+    fn should_replace_nul_bytes() {
+        unsafe {
+            let s = into_c_string("a\0b".to_string());
+            assert_eq!(take_string(s), "a\u{fffd}b");
+        }
+    }
+}
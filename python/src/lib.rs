@@ -0,0 +1,178 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Python bindings to the decoder and metadata
+//!
+//! Decoded values are returned as native dicts and lists, with the same
+//! shape as their JSON serialization
+//!
+//! ```python
+//! import desub
+//!
+//! metadata = desub.Metadata(open("metadata.bin", "rb").read())
+//! decoder = desub.Decoder.from_json(open("decoder.json").read())
+//! decoder.register_metadata(1031, metadata)
+//! events = decoder.decode_events(1031, raw_events)
+//! ```
+
+use desub_core::{
+    decoder::Decoder as CoreDecoder, metadata::Metadata as CoreMetadata, RuntimeVersion,
+};
+use pyo3::{
+    exceptions::ValueError,
+    prelude::*,
+    types::{PyDict, PyList},
+};
+use serde_json::Value;
+
+#[pyclass(unsendable)]
+pub struct Decoder {
+    inner: CoreDecoder,
+}
+
+#[pymethods]
+impl Decoder {
+    /// a decoder without any registered runtime version
+    #[new]
+    fn new() -> Self {
+        Decoder {
+            inner: CoreDecoder::new(),
+        }
+    }
+
+    /// load a decoder serialized with `to_json`
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = CoreDecoder::from_json(json).map_err(to_py_err)?;
+        Ok(Decoder { inner })
+    }
+
+    fn to_json(&self) -> String {
+        self.inner.to_json()
+    }
+
+    /// register the metadata of a runtime version
+    fn register_metadata(&mut self, spec_version: u32, metadata: PyRef<Metadata>) {
+        let version = RuntimeVersion {
+            spec_version,
+            ..Default::default()
+        };
+        self.inner.register_version(metadata.inner.clone(), version);
+    }
+
+    /// decode an extrinsic, including its length prefix
+    fn decode_extrinsic(&self, py: Python, spec: u32, data: &[u8]) -> PyResult<PyObject> {
+        let extrinsic = self.inner.decode_extrinsic(spec, data).map_err(to_py_err)?;
+        to_py_object(py, &extrinsic)
+    }
+
+    /// decode the events stored under the `System Events` storage key
+    fn decode_events(&self, py: Python, spec: u32, data: &[u8]) -> PyResult<PyObject> {
+        let events = self.inner.decode_events(spec, data).map_err(to_py_err)?;
+        to_py_object(py, &events)
+    }
+
+    /// decode the value of the storage entry `storage` of `module`
+    fn decode_storage(
+        &self, py: Python, spec: u32, module: &str, storage: &str, data: &[u8],
+    ) -> PyResult<PyObject> {
+        let value = self
+            .inner
+            .decode_storage(spec, module, storage, data)
+            .map_err(to_py_err)?;
+        to_py_object(py, &value)
+    }
+}
+
+#[pyclass(unsendable)]
+pub struct Metadata {
+    inner: CoreMetadata,
+}
+
+#[pymethods]
+impl Metadata {
+    /// load SCALE-encoded metadata of any supported version
+    #[new]
+    fn new(data: &[u8]) -> PyResult<Self> {
+        let inner = CoreMetadata::from_bytes(data).map_err(to_py_err)?;
+        Ok(Metadata { inner })
+    }
+
+    /// load hex-encoded metadata, as returned by `state_getMetadata`
+    #[staticmethod]
+    fn from_hex(hex: &str) -> PyResult<Self> {
+        let inner = CoreMetadata::from_hex(hex).map_err(to_py_err)?;
+        Ok(Metadata { inner })
+    }
+
+    /// names of all modules, in the order they are declared in the runtime
+    fn modules(&self) -> Vec<String> {
+        self.inner.modules().map(|m| m.name().to_string()).collect()
+    }
+
+    /// the whole metadata, with the schema of its JSON serialization
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        to_py_object(py, &self.inner)
+    }
+}
+
+#[pymodule]
+fn desub(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Decoder>()?;
+    m.add_class::<Metadata>()?;
+    Ok(())
+}
+
+fn to_py_err<E: ToString>(err: E) -> PyErr {
+    ValueError::py_err(err.to_string())
+}
+
+/// convert a value to python through its JSON representation
+fn to_py_object<T: serde::Serialize>(py: Python, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(to_py_err)?;
+    json_to_py(py, &value)
+}
+
+fn json_to_py(py: Python, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.to_object(py),
+        Value::Number(n) => {
+            if let Some(n) = n.as_u64() {
+                n.to_object(py)
+            } else if let Some(n) = n.as_i64() {
+                n.to_object(py)
+            } else {
+                n.as_f64().to_object(py)
+            }
+        }
+        Value::String(s) => s.to_object(py),
+        Value::Array(values) => {
+            let values = values
+                .iter()
+                .map(|v| json_to_py(py, v))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, values).to_object(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(k, json_to_py(py, v)?)?;
+            }
+            dict.to_object(py)
+        }
+    })
+}
@@ -0,0 +1,29 @@
+# Run with `maturin develop && pytest tests` from the `python` directory
+
+import os
+
+import desub
+import pytest
+
+METADATA = os.path.join(os.path.dirname(__file__), "../../core/test/metadata_v9.bin")
+
+
+def metadata():
+    with open(METADATA, "rb") as f:
+        return desub.Metadata(f.read())
+
+
+def test_metadata_to_dict():
+    meta = metadata()
+    modules = meta.to_dict()["modules"]
+    assert [m["name"] for m in modules] == meta.modules()
+    assert "System" in meta.modules()
+
+
+def test_decode_events():
+    decoder = desub.Decoder()
+    decoder.register_metadata(1031, metadata())
+    decoder = desub.Decoder.from_json(decoder.to_json())
+    assert decoder.decode_events(1031, bytes([0])) == []
+    with pytest.raises(ValueError):
+        decoder.decode_events(1, bytes([0]))
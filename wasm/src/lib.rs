@@ -0,0 +1,78 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! JavaScript bindings to the decoder
+//!
+//! A `Decoder` is loaded from the JSON created with `Decoder::to_json`,
+//! so the browser decodes with the exact same definitions as the backend.
+//! Decoded values are returned as plain JavaScript objects, with the same
+//! shape as their JSON serialization
+//!
+//! ```js
+//! import { Decoder } from "desub-wasm";
+//!
+//! const decoder = new Decoder(await (await fetch("/decoder.json")).text());
+//! const extrinsic = decoder.decode_extrinsic(specVersion, bytes);
+//! ```
+
+use desub_core::decoder::Decoder as CoreDecoder;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct Decoder {
+    inner: CoreDecoder,
+}
+
+#[wasm_bindgen]
+impl Decoder {
+    /// load a decoder serialized with `Decoder::to_json`
+    #[wasm_bindgen(constructor)]
+    pub fn new(json: &str) -> Result<Decoder, JsValue> {
+        let inner = CoreDecoder::from_json(json).map_err(to_js_error)?;
+        Ok(Decoder { inner })
+    }
+
+    /// decode an extrinsic, including its length prefix
+    pub fn decode_extrinsic(&self, spec: u32, data: &[u8]) -> Result<JsValue, JsValue> {
+        let extrinsic = self.inner.decode_extrinsic(spec, data).map_err(to_js_error)?;
+        to_js_value(&extrinsic)
+    }
+
+    /// decode the events stored under the `System Events` storage key
+    pub fn decode_events(&self, spec: u32, data: &[u8]) -> Result<JsValue, JsValue> {
+        let events = self.inner.decode_events(spec, data).map_err(to_js_error)?;
+        to_js_value(&events)
+    }
+
+    /// decode the value of the storage entry `storage` of `module`
+    pub fn decode_storage(
+        &self, spec: u32, module: &str, storage: &str, data: &[u8],
+    ) -> Result<JsValue, JsValue> {
+        let value = self
+            .inner
+            .decode_storage(spec, module, storage, data)
+            .map_err(to_js_error)?;
+        to_js_value(&value)
+    }
+}
+
+fn to_js_error(err: desub_core::Error) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+fn to_js_value<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    JsValue::from_serde(value).map_err(|e| JsValue::from_str(&e.to_string()))
+}
@@ -0,0 +1,87 @@
+// Copyright 2019 Parity Technologies (UK) Ltd.
+// This file is part of substrate-desub.
+//
+// substrate-desub is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// substrate-desub is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-desub.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Run with `wasm-pack test --node wasm`
+
+use desub_core::{
+    decoder::{Decoder as CoreDecoder, RustTypeMarker},
+    metadata::Metadata,
+    RuntimeVersion,
+};
+use desub_wasm::Decoder;
+use js_sys::Reflect;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::*;
+
+fn decoder() -> Decoder {
+    let metadata = Metadata::from_bytes(include_bytes!("../../core/test/metadata_v9.bin"))
+        .expect("valid metadata");
+    let version = RuntimeVersion {
+        spec_version: 1031,
+        ..Default::default()
+    };
+    let mut decoder = CoreDecoder::new();
+    decoder.register_version(metadata, version.clone());
+    decoder
+        .register_marker(&version, "Timestamp", "T::Moment", RustTypeMarker::U64)
+        .unwrap();
+    Decoder::new(&decoder.to_json()).unwrap()
+}
+
+/// property `key` of the JavaScript object `value`
+fn get(value: &JsValue, key: &str) -> JsValue {
+    Reflect::get(value, &JsValue::from_str(key)).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn should_decode_extrinsic() {
+    let decoder = decoder();
+    // length prefix, unsigned V4 extrinsic, Timestamp::set(Compact(1337))
+    let ext = [20, 4, 2, 0, 0xe5, 0x14];
+    let extrinsic = decoder.decode_extrinsic(1031, &ext).unwrap();
+    assert!(get(&extrinsic, "signature").is_null());
+    let call = get(&extrinsic, "call");
+    assert_eq!(get(&call, "module").as_string().unwrap(), "Timestamp");
+    assert_eq!(get(&call, "call").as_string().unwrap(), "set");
+    let arg = Reflect::get_u32(&get(&call, "args"), 0).unwrap();
+    assert_eq!(get(&arg, "name").as_string().unwrap(), "now");
+    assert_eq!(get(&arg, "value").as_f64(), Some(1337.0));
+}
+
+#[wasm_bindgen_test]
+fn should_decode_events() {
+    let decoder = decoder();
+    // an empty `Vec<EventRecord>`
+    let events = decoder.decode_events(1031, &[0]).unwrap();
+    assert_eq!(js_sys::Array::from(&events).length(), 0);
+}
+
+#[wasm_bindgen_test]
+fn should_decode_storage() {
+    let decoder = decoder();
+    let now = decoder
+        .decode_storage(1031, "Timestamp", "Now", &1337u64.to_le_bytes())
+        .unwrap();
+    assert_eq!(now.as_f64(), Some(1337.0));
+    assert!(decoder.decode_storage(1031, "Timestamp", "Now", &[0]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn should_fail_on_unknown_spec() {
+    let decoder = decoder();
+    assert!(decoder.decode_events(1, &[0]).is_err());
+    assert!(Decoder::new("not json").is_err());
+}